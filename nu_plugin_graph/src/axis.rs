@@ -0,0 +1,529 @@
+use chrono::{DateTime, Local};
+use nu_protocol::LabeledError;
+use textplots::LabelFormat;
+
+use crate::types::{GraphScale, GraphUnit, GraphValue};
+
+pub const DEFAULT_TICKS: u32 = 5;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Axis {
+    pub unit: GraphUnit,
+    pub scale: GraphScale,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    data: Option<(f64, f64)>,
+    origin: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AxisOptions {
+    pub padding: f64,
+    pub include_zero: bool,
+    pub ticks: u32,
+}
+
+impl Axis {
+    pub fn infer(
+        name: &str,
+        values: impl IntoIterator<Item = GraphValue>,
+        min: Option<GraphValue>,
+        max: Option<GraphValue>,
+        scale: GraphScale,
+    ) -> Result<Self, LabeledError> {
+        let mut unit = GraphUnit::Number;
+        let mut data = None::<(f64, f64)>;
+
+        let values = values.into_iter().map(|value| (value, true));
+        let bounds = min.into_iter().chain(max).map(|value| (value, false));
+        for (value, is_data) in values.chain(bounds) {
+            unit = match (unit, value.unit) {
+                (unit, GraphUnit::Number) => unit,
+                (GraphUnit::Number, value_unit) => value_unit,
+                (unit, value_unit) if unit == value_unit => unit,
+                (unit, value_unit) => {
+                    return Err(LabeledError::new(format!("Mixed units on the {name} axis"))
+                        .with_help(format!(
+                            "got both {} and {} values",
+                            unit.name(),
+                            value_unit.name()
+                        )));
+                }
+            };
+
+            if is_data && value.value.is_finite() && scale.apply(value.value).is_finite() {
+                data = Some(data.map_or((value.value, value.value), |(min, max)| {
+                    (min.min(value.value), max.max(value.value))
+                }));
+            }
+        }
+
+        if unit == GraphUnit::Date && scale != GraphScale::Linear {
+            return Err(
+                LabeledError::new(format!("Unsupported scale on the {name} axis"))
+                    .with_help("datetime values can only be drawn on a linear scale"),
+            );
+        }
+
+        let min = min.map(|min| min.value);
+        let max = max.map(|max| max.value);
+        let origin = match unit {
+            GraphUnit::Date => min.or(data.map(|(min, _)| min)).unwrap_or_default(),
+            _ => 0.0,
+        };
+
+        Ok(Self {
+            unit,
+            scale,
+            min,
+            max,
+            data,
+            origin,
+        })
+    }
+
+    pub fn project(&self, value: f64) -> f32 {
+        self.scale.apply(value - self.origin) as f32
+    }
+
+    pub fn value(&self, projected: f32) -> GraphValue {
+        GraphValue {
+            value: self.scale.invert(projected as f64) + self.origin,
+            unit: self.unit,
+        }
+    }
+
+    pub fn zero(&self) -> Option<f32> {
+        match self.unit == GraphUnit::Number
+            && self.scale == GraphScale::Linear
+            && self.origin == 0.0
+        {
+            true => Some(0.0),
+            false => None,
+        }
+    }
+
+    pub fn data_range(&self) -> Option<(f32, f32)> {
+        self.data
+            .map(|(min, max)| (self.project(min), self.project(max)))
+    }
+
+    pub fn range(&self, data: Option<(f32, f32)>, options: AxisOptions) -> (f32, f32) {
+        let fixed = (
+            self.min.map(|min| self.project(min) as f64),
+            self.max.map(|max| self.project(max) as f64),
+        );
+        let (mut min, mut max) = data
+            .map(|(min, max)| (min as f64, max as f64))
+            .or(fixed.0.zip(fixed.1))
+            .or(fixed.0.map(|min| (min, min)))
+            .or(fixed.1.map(|max| (max, max)))
+            .unwrap_or((0.0, 1.0));
+
+        let zero = self.project(0.0) as f64;
+        let include_zero = options.include_zero && self.unit != GraphUnit::Date && zero.is_finite();
+        if include_zero {
+            min = min.min(zero);
+            max = max.max(zero);
+        }
+
+        if max - min <= 0.0 {
+            let spread = match min == 0.0 {
+                true => self.unit.unit_size(),
+                false => min.abs() * 0.1,
+            };
+            min -= spread;
+            max += spread;
+        }
+
+        let padding = (max - min) * options.padding.max(0.0);
+        if !(include_zero && min == zero) {
+            min -= padding;
+        }
+        if !(include_zero && max == zero) {
+            max += padding;
+        }
+
+        let min = fixed.0.unwrap_or(min);
+        let max = fixed.1.unwrap_or(max);
+
+        let (min, max) = match self.tick_step((min, max), options.ticks) {
+            Some(step) => (
+                match fixed.0 {
+                    Some(min) => min,
+                    None => self.relative((self.absolute(min) / step).floor() * step),
+                },
+                match fixed.1 {
+                    Some(max) => max,
+                    None => self.relative((self.absolute(max) / step).ceil() * step),
+                },
+            ),
+            None => (min, max),
+        };
+
+        (min as f32, max as f32)
+    }
+
+    pub fn ticks(&self, (min, max): (f32, f32), count: u32) -> Vec<f32> {
+        let (min, max) = (min as f64, max as f64);
+        let Some(step) = self.tick_step((min, max), count) else {
+            return vec![];
+        };
+
+        let epsilon = step * 1e-6;
+        let first = ((self.absolute(min) - epsilon) / step).ceil() as i64;
+        let last = ((self.absolute(max) + epsilon) / step).floor() as i64;
+
+        match self.scale {
+            GraphScale::Symlog => (first - 1..=last + 1)
+                .map(|i| match i {
+                    0 => 0.0,
+                    i => {
+                        let decade = 10f64.powf(i.unsigned_abs() as f64 * step - 1.0);
+                        self.scale.apply(i.signum() as f64 * decade)
+                    }
+                })
+                .filter(|tick| (min - epsilon..=max + epsilon).contains(tick))
+                .take(count as usize * 3)
+                .map(|tick| tick as f32)
+                .collect(),
+            _ => (first..=last)
+                .take(count as usize * 3)
+                .map(|i| self.relative(i as f64 * step) as f32)
+                .collect(),
+        }
+    }
+
+    pub fn samples(&self, count: usize, (min, max): (f32, f32)) -> Vec<GraphValue> {
+        if self.data.is_none() && (self.min.is_none() || self.max.is_none()) {
+            return vec![];
+        }
+
+        let (min, max) = (min as f64, max as f64);
+        let step = (max - min) / count.saturating_sub(1).max(1) as f64;
+
+        (0..count)
+            .map(|i| GraphValue {
+                value: self.origin + self.scale.invert(min + step * i as f64),
+                unit: self.unit,
+            })
+            .collect()
+    }
+
+    pub fn format(&self, value: f32) -> String {
+        self.unit
+            .format(self.origin + self.scale.invert(value as f64), self.span())
+    }
+
+    pub fn label_format(self) -> LabelFormat {
+        LabelFormat::Custom(Box::new(move |value| self.format(value)))
+    }
+
+    fn span(&self) -> f64 {
+        let data = self.data.unzip();
+        match (self.min.or(data.0), self.max.or(data.1)) {
+            (Some(min), Some(max)) => max - min,
+            _ => 0.0,
+        }
+    }
+
+    fn tick_step(&self, (min, max): (f64, f64), count: u32) -> Option<f64> {
+        let raw = (max - min) / count.checked_sub(1).filter(|steps| *steps > 0)? as f64;
+        if !raw.is_finite() || raw <= 0.0 {
+            return None;
+        }
+
+        Some(match self.scale {
+            GraphScale::Linear => self.unit.nice_step(raw),
+            _ => nice_step(raw.max(1.0)).ceil(),
+        })
+    }
+
+    fn absolute(&self, value: f64) -> f64 {
+        match self.scale {
+            GraphScale::Linear => self.origin + value,
+            _ => value,
+        }
+    }
+
+    fn relative(&self, value: f64) -> f64 {
+        match self.scale {
+            GraphScale::Linear => value - self.origin,
+            _ => value,
+        }
+    }
+}
+
+impl GraphUnit {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GraphUnit::Number => "number",
+            GraphUnit::Date => "datetime",
+            GraphUnit::Duration => "duration",
+            GraphUnit::Filesize => "filesize",
+        }
+    }
+
+    fn unit_size(&self) -> f64 {
+        match self {
+            GraphUnit::Number => 1.0,
+            GraphUnit::Date | GraphUnit::Duration => 1e9,
+            GraphUnit::Filesize => 1024.0,
+        }
+    }
+
+    fn nice_step(&self, raw: f64) -> f64 {
+        const TIME_STEPS: [f64; 23] = [
+            1e9,
+            2e9,
+            5e9,
+            10e9,
+            15e9,
+            30e9,
+            60e9,
+            120e9,
+            300e9,
+            600e9,
+            900e9,
+            1_800e9,
+            3_600e9,
+            7_200e9,
+            10_800e9,
+            21_600e9,
+            43_200e9,
+            NANOS_IN_DAY,
+            2.0 * NANOS_IN_DAY,
+            7.0 * NANOS_IN_DAY,
+            14.0 * NANOS_IN_DAY,
+            30.0 * NANOS_IN_DAY,
+            90.0 * NANOS_IN_DAY,
+        ];
+
+        match self {
+            GraphUnit::Date | GraphUnit::Duration if raw >= 1e9 => TIME_STEPS
+                .into_iter()
+                .find(|step| *step >= raw)
+                .unwrap_or_else(|| nice_step(raw / NANOS_IN_DAY) * NANOS_IN_DAY),
+            GraphUnit::Filesize if raw >= 1024.0 => {
+                let base = 1024f64.powf(raw.log(1024.0).floor());
+                nice_step(raw / base) * base
+            }
+            _ => nice_step(raw),
+        }
+    }
+
+    pub fn format(&self, value: f64, range: f64) -> String {
+        match self {
+            GraphUnit::Number => format_number(value, range),
+            GraphUnit::Date => format_date(value, range),
+            GraphUnit::Duration => format_duration(value),
+            GraphUnit::Filesize => format_filesize(value),
+        }
+    }
+}
+
+const NANOS_IN_DAY: f64 = 86_400e9;
+
+fn nice_step(raw: f64) -> f64 {
+    let magnitude = 10f64.powf(raw.log10().floor());
+
+    [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= raw)
+        .unwrap_or(10.0 * magnitude)
+}
+
+fn format_number(value: f64, range: f64) -> String {
+    let decimals = match range > 0.0 && range.is_finite() {
+        true => (2.0 - range.log10().floor()).clamp(1.0, 10.0) as usize,
+        false => 1,
+    };
+    let value = format!("{value:.decimals$}");
+
+    match value.contains('.') {
+        true => match value.trim_end_matches('0').trim_end_matches('.') {
+            "-0" => "0".into(),
+            value => value.into(),
+        },
+        false => value,
+    }
+}
+
+fn format_date(nanos: f64, range: f64) -> String {
+    let resolution = match range >= 60e9 {
+        true => 1e9,
+        false => 1e6,
+    };
+    let nanos = (nanos / resolution).round() * resolution;
+    let date = DateTime::from_timestamp_nanos(nanos as i64).with_timezone(&Local);
+    let format = match range {
+        range if range >= 2.0 * NANOS_IN_DAY => "%Y-%m-%d",
+        range if range >= NANOS_IN_DAY / 2.0 => "%m-%d %H:%M",
+        range if range >= 60e9 => "%H:%M",
+        _ => "%H:%M:%S",
+    };
+
+    date.format(format).to_string()
+}
+
+fn format_duration(nanos: f64) -> String {
+    const UNITS: [(&str, f64); 8] = [
+        ("wk", 7.0 * NANOS_IN_DAY),
+        ("day", NANOS_IN_DAY),
+        ("hr", 3_600e9),
+        ("min", 60e9),
+        ("sec", 1e9),
+        ("ms", 1e6),
+        ("µs", 1e3),
+        ("ns", 1.0),
+    ];
+
+    let (name, size) = UNITS
+        .iter()
+        .find(|(_, size)| nanos.abs() >= *size)
+        .unwrap_or(&UNITS[UNITS.len() - 1]);
+
+    format!("{}{name}", trim_float(nanos / size))
+}
+
+fn format_filesize(bytes: f64) -> String {
+    const UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+    let (value, name) = UNITS
+        .iter()
+        .skip(1)
+        .fold((bytes, UNITS[0]), |(value, name), unit| {
+            match value.abs() >= 1024.0 {
+                true => (value / 1024.0, *unit),
+                false => (value, name),
+            }
+        });
+
+    format!("{} {name}", trim_float(value))
+}
+
+fn trim_float(value: f64) -> String {
+    let value = format!("{value:.1}");
+
+    match value.strip_suffix(".0") {
+        Some(value) => value.into(),
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: AxisOptions = AxisOptions {
+        padding: 0.0,
+        include_zero: false,
+        ticks: DEFAULT_TICKS,
+    };
+
+    fn numbers(values: &[f64]) -> Vec<GraphValue> {
+        values.iter().copied().map(GraphValue::number).collect()
+    }
+
+    fn value(value: f64, unit: GraphUnit) -> GraphValue {
+        GraphValue { value, unit }
+    }
+
+    fn linear(values: &[f64], min: Option<f64>, max: Option<f64>) -> Axis {
+        Axis::infer(
+            "y",
+            numbers(values),
+            min.map(GraphValue::number),
+            max.map(GraphValue::number),
+            GraphScale::Linear,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn snaps_bounds_to_the_tick_step() {
+        let axis = linear(&[0.3, 4.0, 9.7], None, None);
+        let range = axis.range(axis.data_range(), OPTIONS);
+
+        assert_eq!(range, (0.0, 10.0));
+        assert_eq!(axis.ticks(range, DEFAULT_TICKS), [0.0, 2.5, 5.0, 7.5, 10.0]);
+    }
+
+    #[test]
+    fn keeps_a_one_sided_bound_and_snaps_the_other() {
+        let axis = linear(&[3.0, 9.7], Some(2.0), None);
+        assert_eq!(axis.range(axis.data_range(), OPTIONS), (2.0, 10.0));
+
+        let axis = linear(&[], Some(2.0), None);
+        assert_eq!(axis.range(axis.data_range(), OPTIONS), (2.0, 2.2));
+    }
+
+    #[test]
+    fn includes_zero_above_negative_data() {
+        let axis = linear(&[-7.0, -3.0], None, None);
+        let options = AxisOptions {
+            include_zero: true,
+            ..OPTIONS
+        };
+
+        assert_eq!(axis.range(axis.data_range(), options), (-8.0, 0.0));
+        assert_eq!(axis.range(axis.data_range(), OPTIONS), (-7.0, -3.0));
+    }
+
+    fn log_ticks(scale: GraphScale, values: &[f64]) -> Vec<String> {
+        let axis = Axis::infer("y", numbers(values), None, None, scale).unwrap();
+        let range = axis.range(axis.data_range(), OPTIONS);
+
+        axis.ticks(range, DEFAULT_TICKS)
+            .iter()
+            .map(|tick| axis.format(*tick))
+            .collect()
+    }
+
+    #[test]
+    fn ticks_a_log_scale_by_decades() {
+        assert_eq!(
+            log_ticks(GraphScale::Log10, &[1.0, 20.0, 1000.0]),
+            ["1", "10", "100", "1000"]
+        );
+        assert_eq!(
+            log_ticks(GraphScale::Log10, &[1.0, 20.0, 100.0]),
+            ["1", "10", "100"]
+        );
+    }
+
+    #[test]
+    fn ticks_a_log_scale_by_whole_powers() {
+        assert_eq!(
+            log_ticks(GraphScale::Log2, &[1.0, 100.0]),
+            ["1", "4", "16", "64", "256"]
+        );
+        assert_eq!(
+            log_ticks(GraphScale::Log2, &[1.0, 8.0]),
+            ["1", "2", "4", "8"]
+        );
+        assert_eq!(
+            log_ticks(GraphScale::Symlog, &[0.0, 1000.0]),
+            ["0", "1", "10", "100", "1000"]
+        );
+    }
+
+    #[test]
+    fn rejects_mixed_units() {
+        let values = [
+            value(1e9, GraphUnit::Duration),
+            value(1024.0, GraphUnit::Filesize),
+        ];
+        let error = Axis::infer("y", values, None, None, GraphScale::Linear).unwrap_err();
+        assert_eq!(error.msg, "Mixed units on the y axis");
+
+        let bound = Some(value(0.0, GraphUnit::Date));
+        let values = [value(1e9, GraphUnit::Duration)];
+        assert!(Axis::infer("y", values, bound, None, GraphScale::Linear).is_err());
+
+        let values = [GraphValue::number(1.0), value(1e9, GraphUnit::Duration)];
+        let axis = Axis::infer("y", values, None, None, GraphScale::Linear).unwrap();
+        assert_eq!(axis.unit, GraphUnit::Duration);
+    }
+}
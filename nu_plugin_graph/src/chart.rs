@@ -0,0 +1,942 @@
+use std::collections::HashMap;
+
+use nu_protocol::{LabeledError, Span, Value};
+
+use crate::{
+    axis::{Axis, AxisOptions, DEFAULT_TICKS},
+    legend::Legend,
+    render::{render_kitty, render_png, render_sixel, render_svg, render_text},
+    theme::Theme,
+    types::{
+        GraphAnnotation, GraphAnnotationType, GraphAxisStyle, GraphConfig, GraphFormat,
+        GraphLineColor, GraphLineConfig, GraphPoint, GraphSize, GraphStacking, GraphType,
+        GraphUnit, GraphValue, GraphYAxis,
+    },
+};
+
+pub const DEFAULT_WIDTH: u32 = 120;
+pub const DEFAULT_HEIGHT: u32 = 60;
+pub const MAX_SIZE: u32 = 1000;
+
+pub struct Plot {
+    pub width: u32,
+    pub height: u32,
+
+    pub x_axis: Axis,
+    pub x_range: (f32, f32),
+    pub x_ticks: Vec<f32>,
+    pub x_style: GraphAxisStyle,
+    pub x_label: String,
+
+    pub y_axis: Axis,
+    pub y_range: (f32, f32),
+    pub y_ticks: Vec<f32>,
+    pub y_style: GraphAxisStyle,
+    pub y_label: String,
+
+    pub y2: Option<PlotAxis>,
+
+    pub legend: Legend,
+
+    pub no_header: bool,
+    pub no_axis: bool,
+    pub no_borders: bool,
+    pub no_figures: bool,
+
+    pub lines: Vec<PlotLine>,
+    pub annotations: Vec<PlotAnnotation>,
+
+    pub theme: Theme,
+}
+
+pub struct PlotAxis {
+    pub axis: Axis,
+    pub range: (f32, f32),
+    pub ticks: Vec<f32>,
+    pub style: GraphAxisStyle,
+    pub label: String,
+}
+
+impl PlotAxis {
+    pub fn to_primary(&self, value: f32, (min, max): (f32, f32)) -> f32 {
+        let (from, to) = self.range;
+        match to > from {
+            true => min + (value - from) / (to - from) * (max - min),
+            false => (min + max) / 2.0,
+        }
+    }
+}
+
+pub struct PlotLine {
+    pub ty: GraphType,
+    pub color: Option<GraphLineColor>,
+    pub points: Vec<(f32, f32)>,
+    pub base: Option<Vec<f32>>,
+}
+
+impl PlotLine {
+    pub fn fill_at(&self, x: f32) -> Option<(f32, f32)> {
+        let base = self.base.as_ref()?;
+        let i = self.points.windows(2).position(|pair| {
+            let (from, to) = (pair[0].0, pair[1].0);
+            from.min(to) <= x && x <= from.max(to)
+        })?;
+        let ((x1, y1), (x2, y2)) = (self.points[i], self.points[i + 1]);
+
+        match self.ty {
+            GraphType::Bars => Some((base[i + 1], y2)),
+            _ => {
+                let t = match x2 != x1 {
+                    true => (x - x1) / (x2 - x1),
+                    false => 0.0,
+                };
+                let lerp = |from: f32, to: f32| from + (to - from) * t;
+                Some((lerp(base[i], base[i + 1]), lerp(y1, y2)))
+            }
+        }
+    }
+}
+
+pub struct PlotAnnotation {
+    pub kind: PlotAnnotationKind,
+    pub label: Option<String>,
+    pub color: Option<GraphLineColor>,
+    pub style: GraphAxisStyle,
+}
+
+pub enum PlotAnnotationKind {
+    HLine(f32),
+    VLine(f32),
+    Band(f32, f32),
+    Text(f32, f32),
+}
+
+impl Plot {
+    pub fn new(config: GraphConfig, theme: Theme) -> Result<Self, LabeledError> {
+        let (x_axis, x_range) = x_axis(&config)?;
+        let GraphConfig {
+            ty,
+            stacking,
+            width,
+            height,
+            x_style,
+            x_label,
+            x_ticks,
+            y_min,
+            y_max,
+            y_style,
+            y_label,
+            y_scale,
+            y_padding,
+            y_include_zero,
+            y_ticks,
+            y2_min,
+            y2_max,
+            y2_style,
+            y2_label,
+            y2_scale,
+            palette,
+            legend,
+            legend_position,
+            no_header,
+            no_axis,
+            no_borders,
+            no_figures,
+            no_downsample,
+            mut lines,
+            annotations,
+            ..
+        } = config;
+
+        let ty = ty.unwrap_or_default();
+        let width = GraphSize::resolve(width, DEFAULT_WIDTH);
+        let height = GraphSize::resolve(height, DEFAULT_HEIGHT);
+
+        if width < 32 || height < 3 {
+            return Err(LabeledError::new("Graph is too small")
+                .with_help("width should be at least 32 and height at least 3"));
+        }
+
+        if width > MAX_SIZE || height > MAX_SIZE {
+            return Err(LabeledError::new("Graph is too large").with_help(format!(
+                "width and height should be at most {MAX_SIZE}, got {width}x{height}"
+            )));
+        }
+
+        let no_header = no_header.unwrap_or_default();
+        let no_borders = no_borders.unwrap_or_default();
+
+        let stacking = stacking.unwrap_or_default();
+        let bases = stack(&mut lines, stacking);
+        let y_include_zero = y_include_zero.or(Some(stacking != GraphStacking::None));
+
+        let axis_values = |axis: GraphYAxis| {
+            lines
+                .iter()
+                .filter(move |line| line.axis.unwrap_or_default() == axis)
+                .flat_map(|line| line.points.iter().map(|p| p.y))
+        };
+        let y_axis = Axis::infer(
+            "Y",
+            axis_values(GraphYAxis::Left),
+            y_min,
+            y_max,
+            y_scale.unwrap_or_default(),
+        )?;
+        let y2_axis = match lines
+            .iter()
+            .any(|line| line.axis == Some(GraphYAxis::Right))
+        {
+            true => Some(Axis::infer(
+                "right Y",
+                axis_values(GraphYAxis::Right),
+                y2_min,
+                y2_max,
+                y2_scale.unwrap_or_default(),
+            )?),
+            false => None,
+        };
+
+        if palette.is_some() || lines.len() > 1 {
+            let palette = palette.unwrap_or_default();
+            let count = lines.len();
+
+            for (i, line) in lines.iter_mut().enumerate() {
+                line.color = line.color.or_else(|| palette.color(i, count));
+            }
+        }
+
+        let legend = Legend::new(
+            legend,
+            legend_position.unwrap_or_default(),
+            no_borders,
+            no_header,
+            &lines,
+        );
+
+        let annotations = annotations
+            .into_iter()
+            .map(|annotation| project_annotation(annotation, &x_axis, &y_axis))
+            .collect::<Result<Vec<_>, LabeledError>>()?;
+
+        let visible_y_range = |axis: GraphYAxis, y_axis: &Axis| {
+            lines
+                .iter()
+                .filter(|line| line.axis.unwrap_or_default() == axis)
+                .flat_map(|line| line.points.iter())
+                .map(|GraphPoint { x, y }| (x_axis.project(x.value), y_axis.project(y.value)))
+                .filter(|(x, y)| {
+                    x.is_finite() && y.is_finite() && (x_range.0..=x_range.1).contains(x)
+                })
+                .map(|(_, y)| y)
+                .fold(None, |range, y| match range {
+                    None => Some((y, y)),
+                    Some((min, max)) => Some((f32::min(min, y), f32::max(max, y))),
+                })
+        };
+
+        let y_ticks = y_ticks.unwrap_or(DEFAULT_TICKS);
+        let y_range = y_axis.range(
+            visible_y_range(GraphYAxis::Left, &y_axis),
+            AxisOptions {
+                padding: y_padding.unwrap_or_default(),
+                include_zero: y_include_zero.unwrap_or_default(),
+                ticks: y_ticks,
+            },
+        );
+        let y2 = y2_axis.map(|axis| {
+            let range = axis.range(
+                visible_y_range(GraphYAxis::Right, &axis),
+                AxisOptions {
+                    padding: y_padding.unwrap_or_default(),
+                    include_zero: y_include_zero.unwrap_or_default(),
+                    ticks: y_ticks,
+                },
+            );
+
+            PlotAxis {
+                axis,
+                range,
+                ticks: axis.ticks(range, y_ticks),
+                style: y2_style.unwrap_or_default(),
+                label: y2_label.unwrap_or_else(|| "Y2".into()),
+            }
+        });
+
+        let resolution = match no_downsample.unwrap_or_default() {
+            true => None,
+            false => Some(Resolution {
+                x_range,
+                y_range,
+                width,
+                height,
+            }),
+        };
+
+        let lines = lines
+            .into_iter()
+            .zip(bases)
+            .map(
+                |(
+                    GraphLineConfig {
+                        ty: line_ty,
+                        color,
+                        axis,
+                        points,
+                        ..
+                    },
+                    base,
+                )| {
+                    let (y_axis, y2) = match (axis.unwrap_or_default(), &y2) {
+                        (GraphYAxis::Right, Some(y2)) => (&y2.axis, Some(y2)),
+                        _ => (&y_axis, None),
+                    };
+                    let to_primary = |y: f32| match y2 {
+                        Some(y2) => y2.to_primary(y, y_range),
+                        None => y,
+                    };
+                    let project = |GraphPoint { x, y }: &GraphPoint| {
+                        (x_axis.project(x.value), y_axis.project(y.value))
+                    };
+
+                    match base {
+                        Some(base) => {
+                            let bottom = y2.map_or(y_range, |y2| y2.range).0;
+                            let (points, base) = points
+                                .iter()
+                                .zip(base)
+                                .map(|(point, base)| (project(point), y_axis.project(base)))
+                                .filter(|((x, y), _)| x.is_finite() && y.is_finite())
+                                .map(|((x, y), base)| {
+                                    let base = match base.is_finite() {
+                                        true => base,
+                                        false => bottom,
+                                    };
+                                    ((x, to_primary(y)), to_primary(base))
+                                })
+                                .unzip();
+
+                            PlotLine {
+                                ty: match line_ty.unwrap_or(ty) {
+                                    GraphType::Bars => GraphType::Bars,
+                                    _ => GraphType::Lines,
+                                },
+                                color,
+                                points,
+                                base: Some(base),
+                            }
+                        }
+                        None => {
+                            let ty = line_ty.unwrap_or(ty);
+                            let projected = |point: &GraphPoint| {
+                                let (x, y) = project(point);
+                                (x, to_primary(y))
+                            };
+
+                            PlotLine {
+                                ty,
+                                color,
+                                points: sample(ty, &points, projected, resolution.as_ref()),
+                                base: None,
+                            }
+                        }
+                    }
+                },
+            )
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            x_axis,
+            x_range,
+            x_ticks: x_axis.ticks(x_range, x_ticks.unwrap_or(DEFAULT_TICKS)),
+            x_style: x_style.unwrap_or_default(),
+            x_label: x_label.unwrap_or_else(|| "X".into()),
+            y_axis,
+            y_range,
+            y_ticks: y_axis.ticks(y_range, y_ticks),
+            y_style: y_style.unwrap_or_default(),
+            y_label: y_label.unwrap_or_else(|| "Y".into()),
+            y2,
+            legend,
+            no_header,
+            no_axis: no_axis.unwrap_or_default(),
+            no_borders,
+            no_figures: no_figures.unwrap_or_default(),
+            lines,
+            annotations,
+            theme,
+        })
+    }
+
+    pub fn x_axis_position(&self) -> Option<f32> {
+        let (min, max) = self.y_range;
+        self.y_axis.zero().filter(|zero| (min..=max).contains(zero))
+    }
+
+    pub fn y_axis_position(&self) -> Option<f32> {
+        let (min, max) = self.x_range;
+        match self.x_axis.zero() {
+            Some(zero) => Some(zero).filter(|zero| (min..=max).contains(zero)),
+            None => Some(min),
+        }
+    }
+}
+
+struct Resolution {
+    x_range: (f32, f32),
+    y_range: (f32, f32),
+    width: u32,
+    height: u32,
+}
+
+fn sample(
+    ty: GraphType,
+    points: &[GraphPoint],
+    project: impl Fn(&GraphPoint) -> (f32, f32),
+    resolution: Option<&Resolution>,
+) -> Vec<(f32, f32)> {
+    let projected = points
+        .iter()
+        .map(project)
+        .filter(|(x, y)| x.is_finite() && y.is_finite());
+
+    let Some(Resolution {
+        x_range: (x_min, x_max),
+        y_range: (y_min, y_max),
+        width,
+        height,
+    }) = resolution
+    else {
+        return projected.collect();
+    };
+
+    let dx = (x_max - x_min) / *width as f32;
+    if matches!(ty, GraphType::Bars)
+        || points.len() <= (*width as usize + 1) * 4
+        || !(dx.is_finite() && dx > 0.0)
+    {
+        return projected.collect();
+    }
+
+    let columns = *width as i64 + 1;
+    let column = |x: f32| ((x - x_min) / dx).round() as i64;
+
+    if let GraphType::Points = ty {
+        let dy = (y_max - y_min) / *height as f32;
+        let rows = *height as i64 + 1;
+        let mut drawn = vec![false; (columns * rows) as usize];
+
+        return projected
+            .filter(|(x, y)| {
+                let (column, row) = (column(*x), ((y - y_min) / dy).round() as i64);
+                (0..columns).contains(&column)
+                    && (0..rows).contains(&row)
+                    && !std::mem::replace(&mut drawn[(column * rows + row) as usize], true)
+            })
+            .collect();
+    }
+
+    let ascending = points.is_sorted_by(|a, b| a.x.value <= b.x.value);
+    let descending = points.is_sorted_by(|a, b| a.x.value >= b.x.value);
+    if !ascending && !descending {
+        return projected.collect();
+    }
+
+    // one bucket per column plus one on each side for the points outside of the range, each
+    // keeping the first, lowest, highest and last point along with their position in the input
+    let mut buckets = vec![None::<[(usize, (f32, f32)); 4]>; columns as usize + 2];
+    for (i, point) in projected.enumerate() {
+        let entry = (i, point);
+        match &mut buckets[(column(point.0) + 1).clamp(0, columns + 1) as usize] {
+            Some([_, min, max, last]) => {
+                if point.1 < min.1.1 {
+                    *min = entry;
+                }
+                if point.1 > max.1.1 {
+                    *max = entry;
+                }
+                *last = entry;
+            }
+            bucket => *bucket = Some([entry; 4]),
+        }
+    }
+
+    if !ascending {
+        buckets.reverse();
+    }
+
+    let mut sampled = Vec::with_capacity(buckets.len() * 4);
+    for mut bucket in buckets.into_iter().flatten() {
+        bucket.sort_unstable_by_key(|(i, _)| *i);
+
+        let mut previous = None;
+        for (i, point) in bucket {
+            if previous != Some(i) {
+                sampled.push(point);
+                previous = Some(i);
+            }
+        }
+    }
+
+    sampled
+}
+
+fn stack(lines: &mut [GraphLineConfig], stacking: GraphStacking) -> Vec<Option<Vec<f64>>> {
+    if stacking == GraphStacking::None {
+        return vec![None; lines.len()];
+    }
+
+    let key =
+        |axis: Option<GraphYAxis>, x: &GraphValue| (axis.unwrap_or_default(), x.value.to_bits());
+    let mut totals = HashMap::new();
+    for line in lines.iter() {
+        for GraphPoint { x, y } in &line.points {
+            *totals.entry(key(line.axis, x)).or_insert(0.0) += y.value;
+        }
+    }
+
+    let mut sums = HashMap::new();
+    lines
+        .iter_mut()
+        .map(|line| {
+            let mut base = vec![];
+            for point in &mut line.points {
+                let k = key(line.axis, &point.x);
+                let sum = sums.entry(k).or_insert(0.0);
+                let (from, to) = (*sum, *sum + point.y.value);
+                *sum = to;
+
+                let scale = match (stacking, totals[&k]) {
+                    (GraphStacking::Percent, 0.0) => 0.0,
+                    (GraphStacking::Percent, total) => 100.0 / total,
+                    _ => 1.0,
+                };
+                base.push(from * scale);
+                point.y.value = to * scale;
+                if stacking == GraphStacking::Percent {
+                    point.y.unit = GraphUnit::Number;
+                }
+            }
+
+            Some(base)
+        })
+        .collect()
+}
+
+fn project_annotation(
+    GraphAnnotation {
+        ty,
+        x,
+        y,
+        from,
+        to,
+        label,
+        color,
+        style,
+    }: GraphAnnotation,
+    x_axis: &Axis,
+    y_axis: &Axis,
+) -> Result<PlotAnnotation, LabeledError> {
+    let name = ty.name();
+    let project = |axis: &Axis, axis_name: &str, field: &str, value: Option<GraphValue>| {
+        let value = value.ok_or_else(|| {
+            LabeledError::new("Incomplete annotation")
+                .with_help(format!("a {name} annotation needs a '{field}' value"))
+        })?;
+
+        match value.unit == GraphUnit::Number || value.unit == axis.unit {
+            true => Ok(axis.project(value.value)),
+            false => Err(
+                LabeledError::new(format!("Mixed units on the {axis_name} axis")).with_help(
+                    format!(
+                        "the {name} annotation has a {} value on an axis of {} values",
+                        value.unit.name(),
+                        axis.unit.name()
+                    ),
+                ),
+            ),
+        }
+    };
+
+    let kind = match ty {
+        GraphAnnotationType::Hline => PlotAnnotationKind::HLine(project(y_axis, "Y", "y", y)?),
+        GraphAnnotationType::Vline => PlotAnnotationKind::VLine(project(x_axis, "X", "x", x)?),
+        GraphAnnotationType::Band => PlotAnnotationKind::Band(
+            project(y_axis, "Y", "from", from)?,
+            project(y_axis, "Y", "to", to)?,
+        ),
+        GraphAnnotationType::Text => {
+            if label.is_none() {
+                return Err(LabeledError::new("Incomplete annotation")
+                    .with_help("a text annotation needs a 'label'"));
+            }
+
+            PlotAnnotationKind::Text(project(x_axis, "X", "x", x)?, project(y_axis, "Y", "y", y)?)
+        }
+    };
+
+    Ok(PlotAnnotation {
+        kind,
+        label,
+        color,
+        style: style.unwrap_or(GraphAxisStyle::Dashed),
+    })
+}
+
+pub fn x_axis(config: &GraphConfig) -> Result<(Axis, (f32, f32)), LabeledError> {
+    let x_axis = Axis::infer(
+        "X",
+        config
+            .lines
+            .iter()
+            .flat_map(|line| line.points.iter().map(|p| p.x)),
+        config.x_min,
+        config.x_max,
+        config.x_scale.unwrap_or_default(),
+    )?;
+    let x_range = x_axis.range(
+        x_axis.data_range(),
+        AxisOptions {
+            padding: config.x_padding.unwrap_or_default(),
+            include_zero: config.x_include_zero.unwrap_or_default(),
+            ticks: config.x_ticks.unwrap_or(DEFAULT_TICKS),
+        },
+    );
+
+    Ok((x_axis, x_range))
+}
+
+pub fn render(
+    config: GraphConfig,
+    format: GraphFormat,
+    theme: Theme,
+    span: Span,
+) -> Result<Value, LabeledError> {
+    let plot = Plot::new(config, theme)?;
+
+    Ok(match format {
+        GraphFormat::Text => Value::string(render_text(&plot), span),
+        GraphFormat::Svg => Value::string(render_svg(&plot), span),
+        GraphFormat::Png => Value::binary(render_png(&plot)?, span),
+        GraphFormat::Sixel => Value::string(render_sixel(&plot), span),
+        GraphFormat::Kitty => Value::string(render_kitty(&plot)?, span),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn white_theme() -> Theme {
+        let config = HashMap::from([("float".into(), Value::test_string("white"))]);
+        Theme::new(&config, true)
+    }
+
+    fn single_line() -> GraphConfig {
+        GraphConfig {
+            ty: Some(GraphType::Lines),
+            width: Some(GraphSize::Dots(40)),
+            height: Some(GraphSize::Dots(20)),
+            lines: vec![GraphLineConfig {
+                points: points(0..10),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn rendered(format: GraphFormat, theme: Theme) -> Value {
+        render(single_line(), format, theme, Span::test_data()).unwrap()
+    }
+
+    const RESOLUTION: Resolution = Resolution {
+        x_range: (0.0, 1000.0),
+        y_range: (-1.0, 1.0),
+        width: 40,
+        height: 10,
+    };
+
+    fn points(xs: impl IntoIterator<Item = i64>) -> Vec<GraphPoint> {
+        xs.into_iter()
+            .map(|x| GraphPoint {
+                x: GraphValue::number(x as f64),
+                y: GraphValue::number((x as f64 / 10.0).sin()),
+            })
+            .collect()
+    }
+
+    fn project(GraphPoint { x, y }: &GraphPoint) -> (f32, f32) {
+        (x.value as f32, y.value as f32)
+    }
+
+    #[test]
+    fn downsamples_sorted_lines_to_columns() {
+        let input = points(-100..1100);
+        let sampled = sample(GraphType::Lines, &input, project, Some(&RESOLUTION));
+
+        assert!(sampled.len() <= (RESOLUTION.width as usize + 3) * 4);
+        assert_eq!(sampled.first(), Some(&project(&input[0])));
+        assert_eq!(sampled.last(), Some(&project(&input[input.len() - 1])));
+        assert!(sampled.is_sorted_by(|a, b| a.0 <= b.0));
+        let highest = |points: &[(f32, f32)]| points.iter().map(|p| p.1).fold(f32::MIN, f32::max);
+        let projected = input.iter().map(project).collect::<Vec<_>>();
+        assert_eq!(highest(&sampled), highest(&projected));
+
+        let reversed = input.into_iter().rev().collect::<Vec<_>>();
+        let sampled = sample(GraphType::Steps, &reversed, project, Some(&RESOLUTION));
+        assert_eq!(sampled.first(), Some(&(1099.0, project(&reversed[0]).1)));
+        assert!(sampled.is_sorted_by(|a, b| a.0 >= b.0));
+    }
+
+    #[test]
+    fn keeps_unsorted_lines() {
+        let input = points((0..1000).map(|i| i * 7919 % 1000));
+        let sampled = sample(GraphType::Lines, &input, project, Some(&RESOLUTION));
+
+        assert_eq!(sampled, input.iter().map(project).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn downsamples_unsorted_points_to_dots() {
+        let input = points((0..1000).map(|i| i * 7919 % 1000));
+        let sampled = sample(GraphType::Points, &input, project, Some(&RESOLUTION));
+
+        assert!(sampled.len() <= 41 * 11);
+        assert!(sampled.len() > 41);
+        assert_eq!(
+            sample(GraphType::Points, &input, project, None).len(),
+            input.len()
+        );
+    }
+
+    #[test]
+    fn uses_the_theme_line_color_only_in_text() {
+        let text = rendered(GraphFormat::Text, white_theme())
+            .into_string()
+            .unwrap();
+        assert!(text.contains("\x1b[38;2;255;255;255m"));
+
+        let svg = rendered(GraphFormat::Svg, white_theme())
+            .into_string()
+            .unwrap();
+        assert!(svg.contains(r#"<polyline points="#));
+        assert!(svg.contains(r#"stroke="currentColor" stroke-width="1.5""#));
+        assert!(!svg.to_lowercase().contains("#ffffff\" stroke-width"));
+
+        assert_eq!(
+            rendered(GraphFormat::Png, white_theme()),
+            rendered(GraphFormat::Png, Theme::default())
+        );
+    }
+
+    #[test]
+    fn draws_the_y_axis_at_zero_only_on_plain_numbers() {
+        let day = 86_400e9;
+        let date = |days: f64| GraphValue {
+            value: 1.7e18 + days * day,
+            unit: GraphUnit::Date,
+        };
+        let config = GraphConfig {
+            width: Some(GraphSize::Dots(40)),
+            height: Some(GraphSize::Dots(20)),
+            x_min: Some(date(-5.0)),
+            no_borders: Some(true),
+            no_header: Some(true),
+            lines: vec![GraphLineConfig {
+                points: (0..5)
+                    .map(|i| GraphPoint {
+                        x: date(i as f64),
+                        y: GraphValue::number(1.0),
+                    })
+                    .collect(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let plot = Plot::new(config.clone(), Theme::default()).unwrap();
+        assert_eq!(plot.y_axis_position(), Some(plot.x_range.0));
+        assert_eq!(plot.x_axis_position(), None);
+
+        let text = render(
+            config,
+            GraphFormat::Text,
+            Theme::default(),
+            Span::test_data(),
+        )
+        .unwrap()
+        .into_string()
+        .unwrap();
+        let rows = text
+            .lines()
+            .take(5)
+            .map(|row| row.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let full_columns = (0..20)
+            .filter(|column| rows.iter().all(|row| row[*column] != '\u{2800}'))
+            .collect::<Vec<_>>();
+        assert_eq!(full_columns, [0]);
+
+        let numbers = Plot::new(single_line(), Theme::default()).unwrap();
+        assert_eq!(numbers.y_axis_position(), Some(0.0));
+        assert_eq!(numbers.x_axis_position(), Some(0.0));
+    }
+
+    #[test]
+    fn rejects_graphs_too_large_to_draw() {
+        let config = GraphConfig {
+            width: Some(GraphSize::Dots(1_000_000_000)),
+            ..single_line()
+        };
+        let error = Plot::new(config, Theme::default()).err().unwrap();
+        assert_eq!(error.msg, "Graph is too large");
+
+        let config = GraphConfig {
+            width: Some(GraphSize::Fraction(0.5)),
+            ..single_line()
+        };
+        let plot = Plot::new(config, Theme::default()).unwrap();
+        assert_eq!(plot.width, DEFAULT_WIDTH / 2);
+    }
+
+    #[test]
+    fn rejects_incomplete_or_mismatched_annotations() {
+        let annotation = |ty, y: Option<GraphValue>, label: Option<&str>| GraphConfig {
+            annotations: vec![GraphAnnotation {
+                ty,
+                x: Some(GraphValue::number(1.0)),
+                y,
+                from: None,
+                to: None,
+                label: label.map(Into::into),
+                color: None,
+                style: None,
+            }],
+            ..single_line()
+        };
+        let error = |config| Plot::new(config, Theme::default()).err().unwrap();
+
+        let plot = Plot::new(
+            annotation(
+                GraphAnnotationType::Hline,
+                Some(GraphValue::number(0.5)),
+                None,
+            ),
+            Theme::default(),
+        )
+        .unwrap();
+        assert!(matches!(
+            plot.annotations[0],
+            PlotAnnotation {
+                kind: PlotAnnotationKind::HLine(0.5),
+                style: GraphAxisStyle::Dashed,
+                ..
+            }
+        ));
+
+        let incomplete = error(annotation(GraphAnnotationType::Hline, None, None));
+        assert_eq!(incomplete.msg, "Incomplete annotation");
+        assert_eq!(
+            incomplete.help.as_deref(),
+            Some("a hline annotation needs a 'y' value")
+        );
+        let unlabeled = error(annotation(
+            GraphAnnotationType::Text,
+            Some(GraphValue::number(0.5)),
+            None,
+        ));
+        assert_eq!(
+            unlabeled.help.as_deref(),
+            Some("a text annotation needs a 'label'")
+        );
+
+        let duration = GraphValue {
+            value: 1e9,
+            unit: GraphUnit::Duration,
+        };
+        let mismatched = error(annotation(GraphAnnotationType::Hline, Some(duration), None));
+        assert_eq!(mismatched.msg, "Mixed units on the Y axis");
+    }
+
+    fn stacked(stacking: GraphStacking) -> (Vec<Vec<f64>>, Vec<Option<Vec<f64>>>) {
+        let line = |ys: [f64; 3], axis| GraphLineConfig {
+            axis,
+            points: ys
+                .into_iter()
+                .enumerate()
+                .map(|(x, y)| GraphPoint {
+                    x: GraphValue::number(x as f64),
+                    y: GraphValue::number(y),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let mut lines = [
+            line([1.0, 2.0, 0.0], None),
+            line([3.0, 6.0, 0.0], Some(GraphYAxis::Left)),
+            line([5.0, 5.0, 5.0], Some(GraphYAxis::Right)),
+        ];
+
+        let bases = stack(&mut lines, stacking);
+        let ys = lines
+            .iter()
+            .map(|line| line.points.iter().map(|point| point.y.value).collect())
+            .collect();
+        (ys, bases)
+    }
+
+    #[test]
+    fn stacks_lines_on_the_same_axis() {
+        let (ys, bases) = stacked(GraphStacking::Stacked);
+        assert_eq!(ys, [[1.0, 2.0, 0.0], [4.0, 8.0, 0.0], [5.0, 5.0, 5.0]]);
+        assert_eq!(
+            bases,
+            [
+                Some(vec![0.0, 0.0, 0.0]),
+                Some(vec![1.0, 2.0, 0.0]),
+                Some(vec![0.0, 0.0, 0.0])
+            ]
+        );
+
+        let (ys, bases) = stacked(GraphStacking::None);
+        assert_eq!(ys[1], [3.0, 6.0, 0.0]);
+        assert_eq!(bases, [None, None, None]);
+    }
+
+    #[test]
+    fn normalizes_stacks_to_percent() {
+        let (ys, bases) = stacked(GraphStacking::Percent);
+        assert_eq!(
+            ys,
+            [
+                [25.0, 25.0, 0.0],
+                [100.0, 100.0, 0.0],
+                [100.0, 100.0, 100.0]
+            ]
+        );
+        assert_eq!(bases[1], Some(vec![25.0, 25.0, 0.0]));
+
+        let config = GraphConfig {
+            stacking: Some(GraphStacking::Percent),
+            lines: vec![GraphLineConfig {
+                points: (1..5)
+                    .map(|x| GraphPoint {
+                        x: GraphValue::number(x as f64),
+                        y: GraphValue {
+                            value: x as f64 * 1024.0,
+                            unit: GraphUnit::Filesize,
+                        },
+                    })
+                    .collect(),
+                ..Default::default()
+            }],
+            ..single_line()
+        };
+        let plot = Plot::new(config, Theme::default()).unwrap();
+        assert_eq!(plot.y_axis.unit, GraphUnit::Number);
+        assert_eq!(plot.y_range, (0.0, 100.0));
+        assert_eq!(plot.lines[0].base, Some(vec![0.0; 4]));
+    }
+}
@@ -0,0 +1,320 @@
+use crate::types::GraphLineColor;
+
+const ATTRIBUTES: [&str; 9] = [
+    "bold",
+    "underline",
+    "italic",
+    "dimmed",
+    "reverse",
+    "blink",
+    "hidden",
+    "strike",
+    "normal",
+];
+
+const ANSI_COLORS: [(&str, (u16, u16, u16)); 21] = [
+    ("black", (0, 0, 0)),
+    ("red", (205, 0, 0)),
+    ("green", (0, 205, 0)),
+    ("yellow", (205, 205, 0)),
+    ("blue", (0, 0, 238)),
+    ("purple", (205, 0, 205)),
+    ("magenta", (205, 0, 205)),
+    ("cyan", (0, 205, 205)),
+    ("white", (229, 229, 229)),
+    ("dark_gray", (127, 127, 127)),
+    ("light_black", (127, 127, 127)),
+    ("light_gray", (229, 229, 229)),
+    ("light_red", (255, 0, 0)),
+    ("light_green", (0, 255, 0)),
+    ("light_yellow", (255, 255, 0)),
+    ("light_blue", (92, 92, 255)),
+    ("light_purple", (255, 0, 255)),
+    ("light_magenta", (255, 0, 255)),
+    ("light_cyan", (0, 255, 255)),
+    ("light_white", (255, 255, 255)),
+    ("default", (229, 229, 229)),
+];
+
+const ANSI_ABBREVIATIONS: [(&str, &str); 12] = [
+    ("b", "black"),
+    ("r", "red"),
+    ("g", "green"),
+    ("y", "yellow"),
+    ("u", "blue"),
+    ("p", "purple"),
+    ("m", "magenta"),
+    ("c", "cyan"),
+    ("w", "white"),
+    ("dgr", "dark_gray"),
+    ("lgr", "light_gray"),
+    ("def", "default"),
+];
+
+const ATTRIBUTE_ABBREVIATIONS: [&str; 8] = ["b", "u", "i", "d", "r", "bl", "h", "s"];
+
+const CSS_COLORS: [(&str, (u16, u16, u16)); 148] = [
+    ("aliceblue", (240, 248, 255)),
+    ("antiquewhite", (250, 235, 215)),
+    ("aqua", (0, 255, 255)),
+    ("aquamarine", (127, 255, 212)),
+    ("azure", (240, 255, 255)),
+    ("beige", (245, 245, 220)),
+    ("bisque", (255, 228, 196)),
+    ("black", (0, 0, 0)),
+    ("blanchedalmond", (255, 235, 205)),
+    ("blue", (0, 0, 255)),
+    ("blueviolet", (138, 43, 226)),
+    ("brown", (165, 42, 42)),
+    ("burlywood", (222, 184, 135)),
+    ("cadetblue", (95, 158, 160)),
+    ("chartreuse", (127, 255, 0)),
+    ("chocolate", (210, 105, 30)),
+    ("coral", (255, 127, 80)),
+    ("cornflowerblue", (100, 149, 237)),
+    ("cornsilk", (255, 248, 220)),
+    ("crimson", (220, 20, 60)),
+    ("cyan", (0, 255, 255)),
+    ("darkblue", (0, 0, 139)),
+    ("darkcyan", (0, 139, 139)),
+    ("darkgoldenrod", (184, 134, 11)),
+    ("darkgray", (169, 169, 169)),
+    ("darkgreen", (0, 100, 0)),
+    ("darkgrey", (169, 169, 169)),
+    ("darkkhaki", (189, 183, 107)),
+    ("darkmagenta", (139, 0, 139)),
+    ("darkolivegreen", (85, 107, 47)),
+    ("darkorange", (255, 140, 0)),
+    ("darkorchid", (153, 50, 204)),
+    ("darkred", (139, 0, 0)),
+    ("darksalmon", (233, 150, 122)),
+    ("darkseagreen", (143, 188, 143)),
+    ("darkslateblue", (72, 61, 139)),
+    ("darkslategray", (47, 79, 79)),
+    ("darkslategrey", (47, 79, 79)),
+    ("darkturquoise", (0, 206, 209)),
+    ("darkviolet", (148, 0, 211)),
+    ("deeppink", (255, 20, 147)),
+    ("deepskyblue", (0, 191, 255)),
+    ("dimgray", (105, 105, 105)),
+    ("dimgrey", (105, 105, 105)),
+    ("dodgerblue", (30, 144, 255)),
+    ("firebrick", (178, 34, 34)),
+    ("floralwhite", (255, 250, 240)),
+    ("forestgreen", (34, 139, 34)),
+    ("fuchsia", (255, 0, 255)),
+    ("gainsboro", (220, 220, 220)),
+    ("ghostwhite", (248, 248, 255)),
+    ("gold", (255, 215, 0)),
+    ("goldenrod", (218, 165, 32)),
+    ("gray", (128, 128, 128)),
+    ("green", (0, 128, 0)),
+    ("greenyellow", (173, 255, 47)),
+    ("grey", (128, 128, 128)),
+    ("honeydew", (240, 255, 240)),
+    ("hotpink", (255, 105, 180)),
+    ("indianred", (205, 92, 92)),
+    ("indigo", (75, 0, 130)),
+    ("ivory", (255, 255, 240)),
+    ("khaki", (240, 230, 140)),
+    ("lavender", (230, 230, 250)),
+    ("lavenderblush", (255, 240, 245)),
+    ("lawngreen", (124, 252, 0)),
+    ("lemonchiffon", (255, 250, 205)),
+    ("lightblue", (173, 216, 230)),
+    ("lightcoral", (240, 128, 128)),
+    ("lightcyan", (224, 255, 255)),
+    ("lightgoldenrodyellow", (250, 250, 210)),
+    ("lightgray", (211, 211, 211)),
+    ("lightgreen", (144, 238, 144)),
+    ("lightgrey", (211, 211, 211)),
+    ("lightpink", (255, 182, 193)),
+    ("lightsalmon", (255, 160, 122)),
+    ("lightseagreen", (32, 178, 170)),
+    ("lightskyblue", (135, 206, 250)),
+    ("lightslategray", (119, 136, 153)),
+    ("lightslategrey", (119, 136, 153)),
+    ("lightsteelblue", (176, 196, 222)),
+    ("lightyellow", (255, 255, 224)),
+    ("lime", (0, 255, 0)),
+    ("limegreen", (50, 205, 50)),
+    ("linen", (250, 240, 230)),
+    ("magenta", (255, 0, 255)),
+    ("maroon", (128, 0, 0)),
+    ("mediumaquamarine", (102, 205, 170)),
+    ("mediumblue", (0, 0, 205)),
+    ("mediumorchid", (186, 85, 211)),
+    ("mediumpurple", (147, 112, 219)),
+    ("mediumseagreen", (60, 179, 113)),
+    ("mediumslateblue", (123, 104, 238)),
+    ("mediumspringgreen", (0, 250, 154)),
+    ("mediumturquoise", (72, 209, 204)),
+    ("mediumvioletred", (199, 21, 133)),
+    ("midnightblue", (25, 25, 112)),
+    ("mintcream", (245, 255, 250)),
+    ("mistyrose", (255, 228, 225)),
+    ("moccasin", (255, 228, 181)),
+    ("navajowhite", (255, 222, 173)),
+    ("navy", (0, 0, 128)),
+    ("oldlace", (253, 245, 230)),
+    ("olive", (128, 128, 0)),
+    ("olivedrab", (107, 142, 35)),
+    ("orange", (255, 165, 0)),
+    ("orangered", (255, 69, 0)),
+    ("orchid", (218, 112, 214)),
+    ("palegoldenrod", (238, 232, 170)),
+    ("palegreen", (152, 251, 152)),
+    ("paleturquoise", (175, 238, 238)),
+    ("palevioletred", (219, 112, 147)),
+    ("papayawhip", (255, 239, 213)),
+    ("peachpuff", (255, 218, 185)),
+    ("peru", (205, 133, 63)),
+    ("pink", (255, 192, 203)),
+    ("plum", (221, 160, 221)),
+    ("powderblue", (176, 224, 230)),
+    ("purple", (128, 0, 128)),
+    ("rebeccapurple", (102, 51, 153)),
+    ("red", (255, 0, 0)),
+    ("rosybrown", (188, 143, 143)),
+    ("royalblue", (65, 105, 225)),
+    ("saddlebrown", (139, 69, 19)),
+    ("salmon", (250, 128, 114)),
+    ("sandybrown", (244, 164, 96)),
+    ("seagreen", (46, 139, 87)),
+    ("seashell", (255, 245, 238)),
+    ("sienna", (160, 82, 45)),
+    ("silver", (192, 192, 192)),
+    ("skyblue", (135, 206, 235)),
+    ("slateblue", (106, 90, 205)),
+    ("slategray", (112, 128, 144)),
+    ("slategrey", (112, 128, 144)),
+    ("snow", (255, 250, 250)),
+    ("springgreen", (0, 255, 127)),
+    ("steelblue", (70, 130, 180)),
+    ("tan", (210, 180, 140)),
+    ("teal", (0, 128, 128)),
+    ("thistle", (216, 191, 216)),
+    ("tomato", (255, 99, 71)),
+    ("turquoise", (64, 224, 208)),
+    ("violet", (238, 130, 238)),
+    ("wheat", (245, 222, 179)),
+    ("white", (255, 255, 255)),
+    ("whitesmoke", (245, 245, 245)),
+    ("yellow", (255, 255, 0)),
+    ("yellowgreen", (154, 205, 50)),
+];
+
+pub fn parse_color(name: &str) -> Option<GraphLineColor> {
+    let name = name.trim().to_lowercase();
+
+    match name.strip_prefix('#') {
+        Some(hex) => parse_hex(hex),
+        None => lookup(&CSS_COLORS, &name)
+            .or_else(|| parse_ansi(&name))
+            .or_else(|| parse_ansi_abbreviation(&name)),
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<GraphLineColor> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |digits: &str| u16::from_str_radix(digits, 16).ok();
+    match hex.len() {
+        3 => Some(GraphLineColor::new(
+            channel(&hex[0..1])? * 17,
+            channel(&hex[1..2])? * 17,
+            channel(&hex[2..3])? * 17,
+        )),
+        6 => Some(GraphLineColor::new(
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        )),
+        _ => None,
+    }
+}
+
+fn parse_ansi(name: &str) -> Option<GraphLineColor> {
+    let mut words = name.split('_').collect::<Vec<_>>();
+    while words
+        .last()
+        .is_some_and(|word| ATTRIBUTES.contains(word) && words.len() > 1)
+    {
+        words.pop();
+    }
+
+    lookup(&ANSI_COLORS, &words.join("_"))
+}
+
+fn parse_ansi_abbreviation(name: &str) -> Option<GraphLineColor> {
+    let find = |name: &str, prefix: &str| {
+        ANSI_ABBREVIATIONS.iter().find_map(|(abbreviation, color)| {
+            let attribute = name.strip_prefix(abbreviation)?;
+
+            match attribute.is_empty() || ATTRIBUTE_ABBREVIATIONS.contains(&attribute) {
+                true => lookup(&ANSI_COLORS, &format!("{prefix}{color}")),
+                false => None,
+            }
+        })
+    };
+
+    find(name, "").or_else(|| find(name.strip_prefix('l')?, "light_"))
+}
+
+fn lookup(colors: &[(&str, (u16, u16, u16))], name: &str) -> Option<GraphLineColor> {
+    colors
+        .iter()
+        .find(|(color, _)| *color == name)
+        .map(|(_, (r, g, b))| GraphLineColor::new(*r, *g, *b))
+}
+
+#[cfg(test)]
+mod tests {
+    use rgb::RGB8;
+
+    use super::*;
+
+    fn rgb(name: &str) -> Option<(u8, u8, u8)> {
+        parse_color(name).map(|color| {
+            let RGB8 { r, g, b } = color.into();
+            (r, g, b)
+        })
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(rgb("#ff8800"), Some((255, 136, 0)));
+        assert_eq!(rgb("#F80"), Some((255, 136, 0)));
+        assert_eq!(rgb(" #00aaFF "), Some((0, 170, 255)));
+        assert_eq!(rgb("#ff880"), None);
+        assert_eq!(rgb("#gg8800"), None);
+    }
+
+    #[test]
+    fn parses_css_names_before_ansi_names() {
+        assert_eq!(rgb("rebeccapurple"), Some((102, 51, 153)));
+        assert_eq!(rgb("Red"), Some((255, 0, 0)));
+        assert_eq!(rgb("purple"), Some((128, 0, 128)));
+        assert_eq!(rgb("nocolor"), None);
+    }
+
+    #[test]
+    fn parses_ansi_names_with_attributes() {
+        assert_eq!(rgb("light_blue"), Some((92, 92, 255)));
+        assert_eq!(rgb("red_bold"), Some((205, 0, 0)));
+        assert_eq!(rgb("dark_gray_underline_italic"), Some((127, 127, 127)));
+        assert_eq!(rgb("bold"), None);
+    }
+
+    #[test]
+    fn parses_nushell_abbreviations() {
+        assert_eq!(rgb("lgr"), Some((229, 229, 229)));
+        assert_eq!(rgb("gb"), Some((0, 205, 0)));
+        assert_eq!(rgb("lu"), Some((92, 92, 255)));
+        assert_eq!(rgb("lrbl"), Some((255, 0, 0)));
+        assert_eq!(rgb("dgr"), Some((127, 127, 127)));
+        assert_eq!(rgb("gx"), None);
+    }
+}
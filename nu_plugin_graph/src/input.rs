@@ -0,0 +1,691 @@
+use std::collections::HashMap;
+
+use nu_protocol::{Config, LabeledError, PipelineData, Record, Span, Spanned, Value};
+
+use crate::types::{GraphLineConfig, GraphPoint, GraphValue};
+
+#[derive(Debug, Default)]
+pub struct InputColumns {
+    pub x: Option<Spanned<String>>,
+    pub y: Vec<Spanned<String>>,
+    pub group_by: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Default)]
+pub struct InputData {
+    pub x_label: Option<String>,
+    pub y_label: Option<String>,
+    pub y_columns: Vec<String>,
+    pub lines: Vec<GraphLineConfig>,
+}
+
+pub fn lines_from_input(
+    input: PipelineData,
+    columns: &InputColumns,
+    head: Span,
+) -> Result<InputData, LabeledError> {
+    let rows = match input.into_value(head)? {
+        Value::Nothing { .. } => return Ok(InputData::default()),
+        Value::List { vals, .. } => vals,
+        value => {
+            return Err(LabeledError::new("Unsupported input").with_label(
+                format!("expected a table or a list, got {}", value.get_type()),
+                value.span(),
+            ));
+        }
+    };
+
+    let Some(first) = rows.first() else {
+        return Ok(InputData::default());
+    };
+
+    match first {
+        Value::Record { val, .. } => lines_from_records(&rows, val, columns),
+        _ => lines_from_values(&rows, columns),
+    }
+}
+
+pub fn series_from_input(
+    input: PipelineData,
+    columns: &InputColumns,
+    head: Span,
+) -> Result<Vec<(String, Vec<GraphValue>)>, LabeledError> {
+    let InputData {
+        y_columns, lines, ..
+    } = lines_from_input(input, columns, head)?;
+
+    Ok(lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, GraphLineConfig { label, points, .. })| {
+            let label = label
+                .or_else(|| y_columns.get(i).cloned())
+                .unwrap_or_else(|| "values".into());
+
+            (label, points.iter().map(|point| point.y).collect())
+        })
+        .collect())
+}
+
+fn lines_from_values(rows: &[Value], columns: &InputColumns) -> Result<InputData, LabeledError> {
+    if let Some(column) = columns
+        .x
+        .iter()
+        .chain(&columns.y)
+        .chain(&columns.group_by)
+        .next()
+    {
+        return Err(LabeledError::new("Column selected for a plain list")
+            .with_label("input is a list of numbers, it has no columns", column.span));
+    }
+
+    let points = rows
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            Ok(GraphPoint {
+                x: GraphValue::number(i as f64),
+                y: value_to_graph(value)?,
+            })
+        })
+        .collect::<Result<Vec<_>, LabeledError>>()?;
+
+    Ok(InputData {
+        x_label: None,
+        y_label: None,
+        y_columns: vec![],
+        lines: vec![GraphLineConfig {
+            points,
+            ..Default::default()
+        }],
+    })
+}
+
+fn lines_from_records(
+    rows: &[Value],
+    first: &Record,
+    columns: &InputColumns,
+) -> Result<InputData, LabeledError> {
+    let x_column = columns.x.as_ref().map(|column| column.item.as_str());
+    let group_column = columns.group_by.as_ref().map(|column| column.item.as_str());
+    let y_columns = match columns.y.is_empty() {
+        false => columns
+            .y
+            .iter()
+            .map(|column| column.item.clone())
+            .collect::<Vec<_>>(),
+        true => first
+            .iter()
+            .filter(|(name, value)| {
+                Some(name.as_str()) != x_column
+                    && Some(name.as_str()) != group_column
+                    && !matches!(GraphValue::try_from_value(value), Ok(None))
+            })
+            .map(|(name, _)| name.clone())
+            .collect(),
+    };
+
+    if y_columns.is_empty() {
+        return Err(LabeledError::new("No numeric columns in input")
+            .with_label("pick the Y columns with --y", rows[0].span()));
+    }
+
+    for column in columns.x.iter().chain(&columns.y).chain(&columns.group_by) {
+        if !rows
+            .iter()
+            .any(|row| row.get_data_by_key(&column.item).is_some())
+        {
+            return Err(LabeledError::new("Column not found").with_label(
+                format!("input has no column '{}'", column.item),
+                column.span,
+            ));
+        }
+    }
+
+    let groups = match group_column {
+        Some(group_column) => group_rows(rows, group_column),
+        None => vec![(None, rows.iter().collect())],
+    };
+
+    let y_columns_count = y_columns.len();
+    let lines = groups
+        .iter()
+        .flat_map(|(group, rows)| {
+            y_columns.iter().map(move |y_column| {
+                let label = match (group, y_columns_count) {
+                    (Some(group), 1) => Some(group.clone()),
+                    (Some(group), _) => Some(format!("{group} ({y_column})")),
+                    (None, _) => None,
+                };
+
+                line_from_rows(rows, x_column, y_column).map(|points| GraphLineConfig {
+                    label,
+                    points,
+                    ..Default::default()
+                })
+            })
+        })
+        .collect::<Result<Vec<_>, LabeledError>>()?;
+
+    Ok(InputData {
+        x_label: x_column.map(Into::into),
+        y_label: Some(y_columns.join(", ")),
+        y_columns,
+        lines,
+    })
+}
+
+fn group_rows<'a>(rows: &'a [Value], group_column: &str) -> Vec<(Option<String>, Vec<&'a Value>)> {
+    let mut indices = HashMap::new();
+
+    rows.iter().fold(vec![], |mut groups, row| {
+        let group = row
+            .get_data_by_key(group_column)
+            .map(|value| value.to_expanded_string(", ", &Config::default()))
+            .unwrap_or_default();
+
+        match indices.get(&group) {
+            Some(&i) => {
+                let (_, rows): &mut (_, Vec<_>) = &mut groups[i];
+                rows.push(row);
+            }
+            None => {
+                indices.insert(group.clone(), groups.len());
+                groups.push((Some(group), vec![row]));
+            }
+        }
+
+        groups
+    })
+}
+
+fn line_from_rows(
+    rows: &[&Value],
+    x_column: Option<&str>,
+    y_column: &str,
+) -> Result<Vec<GraphPoint>, LabeledError> {
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let x = match x_column {
+                Some(x_column) => row.get_data_by_key(x_column),
+                None => Some(Value::float(i as f64, row.span())),
+            };
+            let y = row.get_data_by_key(y_column);
+
+            match (x, y) {
+                (Some(x), Some(y)) if !x.is_nothing() && !y.is_nothing() => Ok(Some(GraphPoint {
+                    x: value_to_graph(&x)?,
+                    y: value_to_graph(&y)?,
+                })),
+                _ => Ok(None),
+            }
+        })
+        .filter_map(Result::transpose)
+        .collect()
+}
+
+fn value_to_graph(value: &Value) -> Result<GraphValue, LabeledError> {
+    GraphValue::try_from_value(value)?.ok_or_else(|| {
+        LabeledError::new("Value can't be plotted").with_label(
+            format!(
+                "expected a number, datetime, duration or filesize, got {}",
+                value.get_type()
+            ),
+            value.span(),
+        )
+    })
+}
+
+pub fn values_from_input(
+    input: PipelineData,
+    column: Option<&Spanned<String>>,
+    head: Span,
+) -> Result<Vec<GraphValue>, LabeledError> {
+    let rows = match input.into_value(head)? {
+        Value::Nothing { .. } => return Ok(vec![]),
+        Value::List { vals, .. } => vals,
+        value => {
+            return Err(LabeledError::new("Unsupported input").with_label(
+                format!("expected a table or a list, got {}", value.get_type()),
+                value.span(),
+            ));
+        }
+    };
+
+    rows.iter()
+        .map(|row| match (row, column) {
+            (Value::Record { .. }, Some(column)) => match row.get_data_by_key(&column.item) {
+                Some(value) if !value.is_nothing() => value_to_graph(&value).map(Some),
+                Some(_) => Ok(None),
+                None => Err(LabeledError::new("Column not found")
+                    .with_label(format!("row has no column '{}'", column.item), row.span())
+                    .with_label("column selected here", column.span)),
+            },
+            (Value::Record { .. }, None) => Err(LabeledError::new("No column selected")
+                .with_label("input is a table, pick a column with --column", row.span())),
+            (Value::Nothing { .. }, _) => Ok(None),
+            (value, _) => value_to_graph(value).map(Some),
+        })
+        .filter_map(Result::transpose)
+        .collect()
+}
+
+#[derive(Debug, Default)]
+pub struct HeatmapColumns {
+    pub x: Option<Spanned<String>>,
+    pub y: Option<Spanned<String>>,
+    pub value: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Default)]
+pub struct HeatmapData {
+    pub x_labels: Vec<String>,
+    pub y_labels: Vec<String>,
+    pub cells: Vec<Vec<Option<GraphValue>>>,
+}
+
+pub fn heatmap_from_input(
+    input: PipelineData,
+    columns: &HeatmapColumns,
+    head: Span,
+) -> Result<HeatmapData, LabeledError> {
+    let rows = match input.into_value(head)? {
+        Value::Nothing { .. } => return Ok(HeatmapData::default()),
+        Value::List { vals, .. } => vals,
+        value => {
+            return Err(LabeledError::new("Unsupported input").with_label(
+                format!(
+                    "expected a table or a list of lists, got {}",
+                    value.get_type()
+                ),
+                value.span(),
+            ));
+        }
+    };
+
+    match rows.first() {
+        Some(Value::Record { .. }) => heatmap_from_records(&rows, columns),
+        Some(_) => heatmap_from_lists(&rows, columns),
+        None => Ok(HeatmapData::default()),
+    }
+}
+
+fn heatmap_from_lists(
+    rows: &[Value],
+    columns: &HeatmapColumns,
+) -> Result<HeatmapData, LabeledError> {
+    if let Some(column) = columns
+        .x
+        .iter()
+        .chain(&columns.y)
+        .chain(&columns.value)
+        .next()
+    {
+        return Err(LabeledError::new("Column selected for a list of lists")
+            .with_label("input is a matrix, it has no columns", column.span));
+    }
+
+    let cells = rows
+        .iter()
+        .map(|row| match row {
+            Value::List { vals, .. } => vals
+                .iter()
+                .map(|value| match value.is_nothing() {
+                    true => Ok(None),
+                    false => value_to_graph(value).map(Some),
+                })
+                .collect::<Result<Vec<_>, _>>(),
+            row => Err(LabeledError::new("Unsupported row").with_label(
+                format!("expected a list of values, got {}", row.get_type()),
+                row.span(),
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let width = cells.iter().map(Vec::len).max().unwrap_or_default();
+
+    Ok(HeatmapData {
+        x_labels: (0..width).map(|x| x.to_string()).collect(),
+        y_labels: (0..cells.len()).map(|y| y.to_string()).collect(),
+        cells: cells
+            .into_iter()
+            .map(|mut row| {
+                row.resize(width, None);
+                row
+            })
+            .collect(),
+    })
+}
+
+fn heatmap_from_records(
+    rows: &[Value],
+    columns: &HeatmapColumns,
+) -> Result<HeatmapData, LabeledError> {
+    let column = |column: &Option<Spanned<String>>, default: &str| {
+        column
+            .as_ref()
+            .map_or(default.to_string(), |column| column.item.clone())
+    };
+    let (x_column, y_column, value_column) = (
+        column(&columns.x, "x"),
+        column(&columns.y, "y"),
+        column(&columns.value, "value"),
+    );
+
+    let mut x_labels = vec![];
+    let mut y_labels = vec![];
+    let mut values = HashMap::new();
+
+    for row in rows {
+        let get = |column: &str| {
+            row.get_data_by_key(column).ok_or_else(|| {
+                LabeledError::new("Column not found")
+                    .with_label(format!("row has no column '{column}'"), row.span())
+            })
+        };
+
+        let x = label_index(&mut x_labels, &get(&x_column)?);
+        let y = label_index(&mut y_labels, &get(&y_column)?);
+        let value = get(&value_column)?;
+        if value.is_nothing() {
+            continue;
+        }
+
+        if values.insert((x, y), value_to_graph(&value)?).is_some() {
+            return Err(LabeledError::new("Duplicate heatmap cell")
+                .with_label(
+                    format!(
+                        "cell ({}, {}) appears more than once",
+                        x_labels[x], y_labels[y]
+                    ),
+                    row.span(),
+                )
+                .with_help("aggregate the rows first, e.g. with group-by"));
+        }
+    }
+
+    let cells = (0..y_labels.len())
+        .map(|y| {
+            (0..x_labels.len())
+                .map(|x| values.get(&(x, y)).copied())
+                .collect()
+        })
+        .collect();
+
+    Ok(HeatmapData {
+        x_labels,
+        y_labels,
+        cells,
+    })
+}
+
+fn label_index(labels: &mut Vec<String>, value: &Value) -> usize {
+    let label = value.to_expanded_string(", ", &Config::default());
+
+    match labels.iter().position(|existing| *existing == label) {
+        Some(i) => i,
+        None => {
+            labels.push(label);
+            labels.len() - 1
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CandleColumns {
+    pub x: Option<Spanned<String>>,
+    pub open: Option<Spanned<String>>,
+    pub high: Option<Spanned<String>>,
+    pub low: Option<Spanned<String>>,
+    pub close: Option<Spanned<String>>,
+    pub volume: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Candle {
+    pub x: Value,
+    pub open: GraphValue,
+    pub high: GraphValue,
+    pub low: GraphValue,
+    pub close: GraphValue,
+    pub volume: Option<GraphValue>,
+}
+
+pub fn candles_from_input(
+    input: PipelineData,
+    columns: &CandleColumns,
+    head: Span,
+) -> Result<Vec<Candle>, LabeledError> {
+    let rows = match input.into_value(head)? {
+        Value::Nothing { .. } => return Ok(vec![]),
+        Value::List { vals, .. } => vals,
+        value => {
+            return Err(LabeledError::new("Unsupported input").with_label(
+                format!("expected a table, got {}", value.get_type()),
+                value.span(),
+            ));
+        }
+    };
+
+    let column = |column: &Option<Spanned<String>>, default: &str| {
+        column
+            .as_ref()
+            .map_or(default.to_string(), |column| column.item.clone())
+    };
+    let (open_column, high_column, low_column, close_column) = (
+        column(&columns.open, "open"),
+        column(&columns.high, "high"),
+        column(&columns.low, "low"),
+        column(&columns.close, "close"),
+    );
+
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            if !matches!(row, Value::Record { .. }) {
+                return Err(LabeledError::new("Unsupported row").with_label(
+                    format!(
+                        "expected a record of open, high, low and close, got {}",
+                        row.get_type()
+                    ),
+                    row.span(),
+                ));
+            }
+
+            let get = |column: &str| {
+                row.get_data_by_key(column).ok_or_else(|| {
+                    LabeledError::new("Column not found")
+                        .with_label(format!("row has no column '{column}'"), row.span())
+                })
+            };
+            let optional = |column: &Option<Spanned<String>>, default: &str| match column {
+                Some(column) => get(&column.item).map(Some),
+                None => Ok(row.get_data_by_key(default)),
+            };
+
+            let values = [&open_column, &high_column, &low_column, &close_column]
+                .into_iter()
+                .map(|column| get(column))
+                .collect::<Result<Vec<_>, _>>()?;
+            if values.iter().any(Value::is_nothing) {
+                return Ok(None);
+            }
+
+            let volume = match optional(&columns.volume, "volume")? {
+                Some(volume) if !volume.is_nothing() => Some(value_to_graph(&volume)?),
+                _ => None,
+            };
+
+            Ok(Some(Candle {
+                x: optional(&columns.x, "x")?.unwrap_or_else(|| Value::int(i as i64, row.span())),
+                open: value_to_graph(&values[0])?,
+                high: value_to_graph(&values[1])?,
+                low: value_to_graph(&values[2])?,
+                close: value_to_graph(&values[3])?,
+                volume,
+            }))
+        })
+        .filter_map(Result::transpose)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use nu_protocol::{Span, record};
+
+    use crate::types::GraphUnit;
+
+    use super::*;
+
+    type Line = (Option<String>, Vec<(f64, f64)>);
+
+    fn column(name: &str) -> Spanned<String> {
+        Spanned {
+            item: name.into(),
+            span: Span::test_data(),
+        }
+    }
+
+    fn table() -> PipelineData {
+        let row = |day: i64, host: &str, cpu: f64, mem: i64| {
+            Value::test_record(record! {
+                "day" => Value::test_int(day),
+                "host" => Value::test_string(host),
+                "cpu" => Value::test_float(cpu),
+                "mem" => Value::test_filesize(mem),
+            })
+        };
+
+        PipelineData::Value(
+            Value::test_list(vec![
+                row(1, "a", 0.5, 1024),
+                row(1, "b", 0.25, 2048),
+                row(2, "a", 0.75, 4096),
+            ]),
+            None,
+        )
+    }
+
+    fn lines(input: PipelineData, columns: &InputColumns) -> Vec<Line> {
+        lines_from_input(input, columns, Span::test_data())
+            .unwrap()
+            .lines
+            .into_iter()
+            .map(|line| {
+                let points = line
+                    .points
+                    .iter()
+                    .map(|point| (point.x.value, point.y.value))
+                    .collect();
+                (line.label, points)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn draws_every_numeric_column_of_a_table() {
+        let data = lines_from_input(table(), &InputColumns::default(), Span::test_data()).unwrap();
+
+        assert_eq!(data.y_columns, ["day", "cpu", "mem"]);
+        assert_eq!(data.y_label.as_deref(), Some("day, cpu, mem"));
+        assert_eq!(data.x_label, None);
+        assert_eq!(data.lines[2].points[1].y.unit, GraphUnit::Filesize);
+        assert_eq!(
+            lines(table(), &InputColumns::default())[1].1,
+            [(0.0, 0.5), (1.0, 0.25), (2.0, 0.75)]
+        );
+    }
+
+    #[test]
+    fn picks_the_x_and_y_columns() {
+        let columns = InputColumns {
+            x: Some(column("day")),
+            y: vec![column("cpu")],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            lines(table(), &columns),
+            [(None, vec![(1.0, 0.5), (1.0, 0.25), (2.0, 0.75)])]
+        );
+
+        let columns = InputColumns {
+            y: vec![column("disk")],
+            ..Default::default()
+        };
+        let error = lines_from_input(table(), &columns, Span::test_data()).unwrap_err();
+        assert_eq!(error.msg, "Column not found");
+    }
+
+    #[test]
+    fn splits_a_table_into_one_line_per_group() {
+        let columns = InputColumns {
+            x: Some(column("day")),
+            y: vec![column("cpu")],
+            group_by: Some(column("host")),
+        };
+        assert_eq!(
+            lines(table(), &columns),
+            [
+                (Some("a".into()), vec![(1.0, 0.5), (2.0, 0.75)]),
+                (Some("b".into()), vec![(1.0, 0.25)]),
+            ]
+        );
+
+        let columns = InputColumns {
+            x: Some(column("day")),
+            group_by: Some(column("host")),
+            ..Default::default()
+        };
+        let labels = lines(table(), &columns)
+            .into_iter()
+            .map(|(label, _)| label.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["a (cpu)", "a (mem)", "b (cpu)", "b (mem)"]);
+    }
+
+    #[test]
+    fn draws_a_list_of_numbers_against_their_index() {
+        let list = || {
+            PipelineData::Value(
+                Value::test_list(vec![
+                    Value::test_int(3),
+                    Value::test_float(1.5),
+                    Value::test_int(-2),
+                ]),
+                None,
+            )
+        };
+
+        assert_eq!(
+            lines(list(), &InputColumns::default()),
+            [(None, vec![(0.0, 3.0), (1.0, 1.5), (2.0, -2.0)])]
+        );
+
+        let columns = InputColumns {
+            x: Some(column("day")),
+            ..Default::default()
+        };
+        let error = lines_from_input(list(), &columns, Span::test_data()).unwrap_err();
+        assert_eq!(error.msg, "Column selected for a plain list");
+    }
+
+    #[test]
+    fn rejects_values_that_cant_be_drawn() {
+        let input = PipelineData::Value(Value::test_int(1), None);
+        let error = lines_from_input(input, &InputColumns::default(), Span::test_data());
+        assert_eq!(error.unwrap_err().msg, "Unsupported input");
+
+        let input = PipelineData::Value(Value::test_list(vec![Value::test_string("a")]), None);
+        let error = lines_from_input(input, &InputColumns::default(), Span::test_data());
+        assert_eq!(error.unwrap_err().msg, "Value can't be plotted");
+
+        let data = lines_from_input(
+            PipelineData::Empty,
+            &InputColumns::default(),
+            Span::test_data(),
+        );
+        assert!(data.unwrap().lines.is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+use nu_ansi_term::Color;
+use rgb::RGB8;
+
+use crate::{
+    types::{GraphLegendPosition, GraphLineColor, GraphLineConfig},
+    util::{pad_visible, side_by_side, visible_width},
+};
+
+const SWATCH: &str = "⣿⣿";
+
+pub struct LegendEntry {
+    pub color: Option<GraphLineColor>,
+    pub label: String,
+}
+
+pub struct Legend {
+    pub position: GraphLegendPosition,
+    pub no_borders: bool,
+    pub no_header: bool,
+    pub entries: Vec<LegendEntry>,
+}
+
+impl Legend {
+    pub fn new(
+        show: Option<bool>,
+        position: GraphLegendPosition,
+        no_borders: bool,
+        no_header: bool,
+        lines: &[GraphLineConfig],
+    ) -> Self {
+        let show_unlabeled = show.unwrap_or_default();
+        let entries = match show {
+            Some(false) => vec![],
+            _ => lines
+                .iter()
+                .enumerate()
+                .filter_map(|(i, GraphLineConfig { label, color, .. })| {
+                    let label = match (label, show_unlabeled) {
+                        (Some(label), _) => label.clone(),
+                        (None, true) => format!("Line {}", i + 1),
+                        (None, false) => return None,
+                    };
+
+                    Some(LegendEntry {
+                        color: *color,
+                        label,
+                    })
+                })
+                .collect(),
+        };
+
+        Self {
+            position,
+            no_borders,
+            no_header,
+            entries,
+        }
+    }
+
+    pub fn text_lines(&self, fallback: Option<GraphLineColor>) -> Option<Vec<String>> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        let entries = self
+            .entries
+            .iter()
+            .map(|LegendEntry { color, label }| {
+                let swatch = match color.or(fallback) {
+                    Some(color) => {
+                        let RGB8 { r, g, b } = color.into();
+                        Color::Rgb(r, g, b).paint(SWATCH).to_string()
+                    }
+                    None => SWATCH.into(),
+                };
+
+                format!("{swatch} {label}")
+            })
+            .collect::<Vec<_>>();
+
+        Some(match (self.no_borders, self.no_header) {
+            (true, true) => entries,
+            (true, false) => std::iter::once("Legend".into()).chain(entries).collect(),
+            (false, no_header) => {
+                let title = match no_header {
+                    true => "",
+                    false => " Legend ",
+                };
+                let inner_width = entries
+                    .iter()
+                    .map(|entry| visible_width(entry))
+                    .chain([visible_width(title)])
+                    .max()
+                    .unwrap_or_default()
+                    + 2;
+
+                std::iter::once(format!(
+                    "┌{title}{}┐",
+                    "─".repeat(inner_width - visible_width(title))
+                ))
+                .chain(
+                    entries
+                        .iter()
+                        .map(|entry| format!("│ {} │", pad_visible(entry, inner_width - 2))),
+                )
+                .chain([format!("└{}┘", "─".repeat(inner_width))])
+                .collect()
+            }
+        })
+    }
+
+    pub fn attach(&self, chart: String, fallback: Option<GraphLineColor>) -> String {
+        let Some(legend) = self.text_lines(fallback) else {
+            return chart;
+        };
+
+        match self.position {
+            GraphLegendPosition::Bottom => {
+                format!("{}\n{}", chart.trim_end_matches('\n'), legend.join("\n"))
+            }
+            GraphLegendPosition::Right => side_by_side(&chart, &legend, 2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legend(show: Option<bool>, no_borders: bool, no_header: bool) -> Legend {
+        let line = |label: Option<&str>| GraphLineConfig {
+            label: label.map(Into::into),
+            ..Default::default()
+        };
+
+        Legend::new(
+            show,
+            GraphLegendPosition::Bottom,
+            no_borders,
+            no_header,
+            &[line(Some("cpu")), line(None), line(Some("memory"))],
+        )
+    }
+
+    #[test]
+    fn frames_the_entries_unless_borders_are_hidden() {
+        assert_eq!(
+            legend(None, false, false).text_lines(None).unwrap(),
+            [
+                "┌ Legend ───┐",
+                "│ ⣿⣿ cpu    │",
+                "│ ⣿⣿ memory │",
+                "└───────────┘",
+            ]
+        );
+        assert_eq!(
+            legend(None, false, true).text_lines(None).unwrap(),
+            [
+                "┌───────────┐",
+                "│ ⣿⣿ cpu    │",
+                "│ ⣿⣿ memory │",
+                "└───────────┘"
+            ]
+        );
+        assert_eq!(
+            legend(None, true, false).text_lines(None).unwrap(),
+            ["Legend", "⣿⣿ cpu", "⣿⣿ memory"]
+        );
+        assert_eq!(
+            legend(None, true, true).text_lines(None).unwrap(),
+            ["⣿⣿ cpu", "⣿⣿ memory"]
+        );
+    }
+
+    #[test]
+    fn names_unlabeled_lines_only_when_asked() {
+        assert_eq!(
+            legend(Some(true), true, true).text_lines(None).unwrap(),
+            ["⣿⣿ cpu", "⣿⣿ Line 2", "⣿⣿ memory"]
+        );
+        assert!(legend(Some(false), true, true).text_lines(None).is_none());
+        assert_eq!(
+            legend(Some(false), true, true).attach("chart".into(), None),
+            "chart"
+        );
+    }
+
+    #[test]
+    fn attaches_below_or_next_to_the_chart() {
+        let mut legend = legend(None, true, true);
+        assert_eq!(
+            legend.attach("ab\ncd\n".into(), None),
+            "ab\ncd\n⣿⣿ cpu\n⣿⣿ memory"
+        );
+
+        legend.position = GraphLegendPosition::Right;
+        assert_eq!(
+            legend.attach("abc\nd\ne".into(), None),
+            "abc  ⣿⣿ cpu\nd    ⣿⣿ memory\ne"
+        );
+    }
+}
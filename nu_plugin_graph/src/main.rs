@@ -1,7 +1,16 @@
+mod axis;
+mod chart;
+mod color;
+mod input;
+mod legend;
 mod plugin;
+mod render;
+mod stats;
+mod theme;
 mod types;
+mod util;
 
-use nu_plugin::{serve_plugin, MsgPackSerializer};
+use nu_plugin::{MsgPackSerializer, serve_plugin};
 use plugin::NuGraphsPlugin;
 
 pub mod built_info {
@@ -1,180 +1,327 @@
-use nu_plugin::PluginCommand;
-use nu_protocol::{Signature, SyntaxShape, Type, Value};
-use textplots::{AxisBuilder, Chart, ColorPlot, Plot, Shape};
+mod bar;
+mod boxplot;
+mod candle;
+mod draw;
+mod grid;
+mod heatmap;
+mod histogram;
+mod spark;
 
-use crate::types::{GraphConfig, GraphLineConfig, GraphPoint, GraphType};
+pub use bar::Bar;
+pub use boxplot::BoxPlot;
+pub use candle::Candlestick;
+pub use draw::Draw;
+pub use grid::Grid;
+pub use heatmap::Heatmap;
+pub use histogram::Histogram;
+pub use spark::Spark;
 
-use super::NuGraphsPlugin;
+use nu_plugin::{EngineInterface, EvaluatedCall};
+use nu_protocol::{FromValue, IntoValue, LabeledError, Span, Spanned, UseAnsiColoring, Value};
+use terminal_size::{Height, Width};
 
-pub struct Draw;
+use crate::{
+    axis::{Axis, DEFAULT_TICKS},
+    chart::{DEFAULT_WIDTH, MAX_SIZE, x_axis},
+    legend::Legend,
+    theme::Theme,
+    types::{
+        GraphConfig, GraphFormat, GraphLegendPosition, GraphPoint, GraphSize, GraphType,
+        GraphValue, GraphYAxis,
+    },
+    util::visible_width,
+};
 
-impl PluginCommand for Draw {
-    type Plugin = NuGraphsPlugin;
+const TERMINAL_MARGIN: (u32, u32) = (12, 4);
+const Y2_MARGIN: u32 = 10;
 
-    fn name(&self) -> &str {
-        "draw"
+fn fit_to_terminal(
+    engine: &EngineInterface,
+    config: &mut GraphConfig,
+    format: GraphFormat,
+) -> Result<(), LabeledError> {
+    if !matches!(format, GraphFormat::Text) {
+        return Ok(());
     }
 
-    fn signature(&self) -> Signature {
-        Signature::build("draw").input_output_type(Type::Nothing, Type::String).required(
-            "graph-config",
-            SyntaxShape::Any,
-            [
-                "\n\tGraph Configuration {",
-                &[
-                    "\ttype: Type of Graph ([points*, lines, steps, bars])",
-                    "width: Width of Graph (default: 120)",
-                    "width: Height of Graph (default: 60)",
-                    "x_min: Minimum Value Of X (default: Minimum value of x across the lines)",
-                    "x_min: Maximum Value Of X (default: Maximum value of x across the lines)",
-                    "x_style: Style of the Graphs X Axis ([none, solid*, dotted, dashed])",
-                    "x_label: Label of the X Axis (default: 'X')",
-                    "y_min: Minimum Value Of Y (default: Minimum value of y across the lines)",
-                    "y_min: Maximum Value Of Y (default: Maximum value of y across the lines)",
-                    "y_style: Style of the Graphs Y Axis ([none, solid*, dotted, dashed])",
-                    "y_label: Label of the Y Axis (default: 'Y')",
-                    "no_header: Don't show header of the graph (default: false)",
-                    "no_axis: Don't show axis of the graph (default: false)",
-                    "no_borders: Don't show border of the graph (default: false)",
-                    "no_figures: Don't show figures of the graph (default: false)",
-                    "lines: [{",
-                    &[
-                        "\tcolor: Colors of the Graph Line ({r: int, g: int, b: int} (0-255)) (default: nothing)",
-                        "points: Points of the Graph Line ([{x: float, y: float}]"
-                    ].join("\n\t\t\t"),
-                    "}]"
-                ].join("\n\t\t"),
-                "}",
-            ]
-            .join("\n\t"),
-        )
+    if let Some((columns, rows)) = terminal_size(engine)? {
+        fit_to_area(config, columns, rows);
     }
 
-    fn description(&self) -> &str {
-        "Draw a chart"
-    }
+    Ok(())
+}
 
-    fn run(
-        &self,
-        _plugin: &Self::Plugin,
-        _engine: &nu_plugin::EngineInterface,
-        call: &nu_plugin::EvaluatedCall,
-        _input: nu_protocol::PipelineData,
-    ) -> Result<nu_protocol::PipelineData, nu_protocol::LabeledError> {
-        let GraphConfig {
-            ty,
-            width,
-            height,
-            x_min,
-            x_max,
-            x_style,
-            x_label,
-            y_min,
-            y_max,
-            y_style,
-            y_label,
-            no_header,
-            no_axis,
-            no_borders,
-            no_figures,
-            lines,
-        } = call.req(0)?;
-
-        let ty = ty.unwrap_or_default();
-        let width = width.unwrap_or(120);
-        let height = height.unwrap_or(60);
-
-        let x_style = x_style.unwrap_or_default();
-        let x_label = x_label.unwrap_or_else(|| "X".into());
-
-        let y_style = y_style.unwrap_or_default();
-        let y_label = y_label.unwrap_or_else(|| "Y".into());
-
-        let no_header = no_header.unwrap_or_default();
-        let no_axis = no_axis.unwrap_or_default();
-        let no_borders = no_borders.unwrap_or_default();
-        let no_figures = no_figures.unwrap_or_default();
-
-        let all_x_bits = lines
-            .iter()
-            .flat_map(|line| line.points.iter().map(|p| p.x.to_bits()))
-            .collect::<Vec<_>>();
-
-        let x_min = x_min
-            .or_else(|| all_x_bits.iter().min().copied().map(f32::from_bits))
-            .unwrap_or(f32::MIN);
-        let x_max = x_max
-            .or_else(|| all_x_bits.iter().max().copied().map(f32::from_bits))
-            .unwrap_or(f32::MAX);
-
-        let mut chart = match (y_min, y_max) {
-            (Some(y_min), Some(y_max)) => {
-                Chart::new_with_y_range(width, height, x_min, x_max, y_min, y_max)
+fn fit_to_area(config: &mut GraphConfig, columns: u32, rows: u32) {
+    let legend = Legend::new(
+        config.legend,
+        config.legend_position.unwrap_or_default(),
+        config.no_borders.unwrap_or_default(),
+        config.no_header.unwrap_or_default(),
+        &config.lines,
+    );
+    let (legend_columns, legend_rows) = match (legend.text_lines(None), legend.position) {
+        (None, _) => (0, 0),
+        (Some(lines), GraphLegendPosition::Bottom) => (0, lines.len() as u32),
+        (Some(lines), GraphLegendPosition::Right) => (
+            lines
+                .iter()
+                .map(|line| visible_width(line))
+                .max()
+                .unwrap_or_default() as u32
+                + 2,
+            0,
+        ),
+    };
+
+    let y2_columns = match config
+        .lines
+        .iter()
+        .any(|line| line.axis == Some(GraphYAxis::Right))
+    {
+        true => Y2_MARGIN,
+        false => 0,
+    };
+
+    let fit = |size: Option<GraphSize>, available: u32, min: u32| match size {
+        Some(GraphSize::Dots(_)) => size,
+        size => Some(GraphSize::Dots(
+            GraphSize::resolve(size, available).clamp(min, MAX_SIZE),
+        )),
+    };
+    config.width = fit(
+        config.width,
+        columns.saturating_sub(TERMINAL_MARGIN.0 + legend_columns + y2_columns) * 2,
+        32,
+    );
+    config.height = fit(
+        config.height,
+        rows.saturating_sub(TERMINAL_MARGIN.1 + legend_rows) * 4,
+        3,
+    );
+}
+
+fn terminal_size(engine: &EngineInterface) -> Result<Option<(u32, u32)>, LabeledError> {
+    let env_size = |name: &str| -> Result<Option<u32>, LabeledError> {
+        Ok(engine.get_env_var(name)?.and_then(|value| match value {
+            Value::Int { val, .. } => u32::try_from(val).ok(),
+            value => value.coerce_str().ok()?.trim().parse().ok(),
+        }))
+    };
+
+    let size = terminal_size::terminal_size_of(std::io::stderr())
+        .map(|(Width(columns), Height(rows))| (columns as u32, rows as u32));
+
+    Ok(match (env_size("COLUMNS")?, env_size("LINES")?, size) {
+        (Some(columns), Some(rows), _) => Some((columns, rows)),
+        (columns, rows, Some((terminal_columns, terminal_rows))) => Some((
+            columns.unwrap_or(terminal_columns),
+            rows.unwrap_or(terminal_rows),
+        )),
+        _ => None,
+    })
+}
+
+fn theme(engine: &EngineInterface) -> Result<Theme, LabeledError> {
+    let config = engine.get_config()?;
+    let env_flag = |name: &str| -> Result<Option<bool>, LabeledError> {
+        Ok(engine
+            .get_env_var(name)?
+            .and_then(|value| value.coerce_bool().ok()))
+    };
+
+    let no_color = engine
+        .get_env_var("NO_COLOR")?
+        .is_some_and(|value| value.coerce_str().is_ok_and(|value| !value.is_empty()));
+
+    let colored = match config.use_ansi_coloring {
+        UseAnsiColoring::True => true,
+        UseAnsiColoring::False => false,
+        UseAnsiColoring::Auto => {
+            match (env_flag("FORCE_COLOR")?, no_color, env_flag("CLICOLOR")?) {
+                (Some(true), _, _) => true,
+                (_, true, _) => false,
+                (_, _, clicolor) => clicolor.unwrap_or(true),
+            }
+        }
+    };
+
+    Ok(Theme::new(&config.color_config, colored))
+}
+
+fn get_all_flags(call: &EvaluatedCall, name: &str) -> Result<Vec<Spanned<String>>, LabeledError> {
+    call.named
+        .iter()
+        .filter(|(flag, _)| flag.item == name)
+        .filter_map(|(_, value)| value.clone())
+        .try_fold(vec![], |mut acc, value| {
+            match value {
+                Value::List { vals, .. } => {
+                    for val in vals {
+                        acc.push(Spanned::from_value(val)?);
+                    }
+                }
+                value => acc.push(Spanned::from_value(value)?),
             }
-            _ => Chart::new(width, height, x_min, x_max),
+
+            Ok(acc)
+        })
+}
+
+fn eval_functions(
+    engine: &EngineInterface,
+    config: &mut GraphConfig,
+    head: Span,
+) -> Result<(), LabeledError> {
+    if config.lines.iter().all(|line| line.function.is_none()) {
+        return Ok(());
+    }
+
+    let (x_axis, x_range) = x_axis(config)?;
+    let xs = x_axis.samples(
+        GraphSize::resolve(config.width, DEFAULT_WIDTH) as usize + 1,
+        x_range,
+    );
+
+    if xs.is_empty() {
+        return Err(LabeledError::new("Can't evaluate a function line")
+            .with_label("no range to evaluate the function over", head)
+            .with_help("set x_min and x_max, or add lines with points"));
+    }
+
+    for line in &mut config.lines {
+        let Some(function) = line.function.take() else {
+            continue;
         };
 
-        let lines = lines
-            .iter()
-            .map(|GraphLineConfig { color, points }| {
-                let shape_list = points
-                    .iter()
-                    .map(|GraphPoint { x, y }| (*x, *y))
-                    .collect::<Vec<_>>();
+        if !line.points.is_empty() {
+            return Err(LabeledError::new("Line has both points and a function")
+                .with_label("use either points or function for a line", head));
+        }
 
-                (color, shape_list)
-            })
-            .collect::<Vec<_>>();
-        let lines_color_shape = lines
+        let function = Spanned {
+            item: function,
+            span: head,
+        };
+
+        line.ty = line.ty.or(Some(GraphType::Lines));
+        line.points = xs
             .iter()
-            .map(|(color, shape_list)| {
-                let shape = match ty {
-                    GraphType::Points => Shape::Points(shape_list),
-                    GraphType::Lines => Shape::Lines(shape_list),
-                    GraphType::Steps => Shape::Steps(shape_list),
-                    GraphType::Bars => Shape::Bars(shape_list),
-                };
-
-                (color, shape)
+            .map(|x| {
+                let y = engine.eval_closure(&function, vec![x.into_value(head)], None)?;
+
+                match y.is_nothing() {
+                    true => Ok(None),
+                    false => Ok(Some(GraphPoint {
+                        x: *x,
+                        y: GraphValue::from_value(y)?,
+                    })),
+                }
             })
-            .collect::<Vec<_>>();
-
-        let prepared_chart = lines_color_shape.iter().fold(
-            chart
-                .x_axis_style(x_style.into())
-                .y_axis_style(y_style.into()),
-            |chart, (color, shape)| match color {
-                Some(color) => chart.linecolorplot(shape, (*color).into()),
-                None => chart.lineplot(shape),
-            },
-        );
-
-        let get_chart_str = |chart: &mut Chart<'_>| {
-            if !no_borders {
-                chart.borders();
-            }
+            .filter_map(Result::transpose)
+            .collect::<Result<Vec<_>, LabeledError>>()?;
+    }
 
-            if !no_axis {
-                chart.axis();
-            }
+    Ok(())
+}
 
-            if !no_figures {
-                chart.figures();
-            }
+struct Scale {
+    axis: Axis,
+    range: (f32, f32),
+    length: usize,
+}
 
-            chart.to_string()
+impl Scale {
+    fn position(&self, value: f32) -> usize {
+        let (min, max) = self.range;
+        let fraction = match max > min {
+            true => (value - min) / (max - min),
+            false => 0.5,
         };
-        let chart_str = get_chart_str(prepared_chart);
 
-        let resulting_str = (match no_header {
-            true => vec![chart_str],
-            false => vec![format!("{y_label}/{x_label}"), chart_str],
-        })
-        .join("\n");
+        ((fraction * (self.length - 1) as f32).round().max(0.0) as usize).min(self.length - 1)
+    }
+
+    fn value_position(&self, value: f64) -> usize {
+        self.position(self.axis.project(value))
+    }
 
-        Ok(nu_protocol::PipelineData::Value(
-            Value::string(resulting_str, call.head),
-            None,
-        ))
+    fn ticks(&self) -> Vec<(usize, String)> {
+        let ticks = match self.axis.ticks(self.range, DEFAULT_TICKS) {
+            ticks if ticks.is_empty() => vec![self.range.0, self.range.1],
+            ticks => ticks,
+        };
+
+        ticks
+            .into_iter()
+            .map(|tick| (self.position(tick), self.axis.format(tick)))
+            .collect()
     }
 }
+
+fn graph_config_description() -> String {
+    [
+        "\n\tGraph Configuration {",
+        &[
+            "\ttype: Type of Graph ([points*, lines, steps, bars])",
+            "stacking: Stack the lines on top of each other as filled areas or stacked bars, percent scaling each x to 100 ([none*, stacked, percent])",
+            "width: Width of Graph in dots (at most 1000), or a fraction like 0.5 of the terminal width for text and of 120 otherwise (default: terminal width for text, 120 otherwise)",
+            "height: Height of Graph in dots (at most 1000), or a fraction like 0.5 of the terminal height for text and of 60 otherwise (default: terminal height for text, 60 otherwise)",
+            "x_min: Minimum Value Of X (default: Minimum value of x across the lines)",
+            "x_max: Maximum Value Of X (default: Maximum value of x across the lines)",
+            "x_style: Style of the Graphs X Axis ([none, solid*, dotted, dashed])",
+            "x_label: Label of the X Axis (default: 'X')",
+            "x_scale: Scale of the X Axis ([linear*, log10, log2, symlog])",
+            "x_padding: Fraction of the X range added on each automatic side (default: 0)",
+            "x_include_zero: Extend the automatic X range to include 0 (default: false)",
+            "x_ticks: Number of rounded ticks on the X Axis, automatic bounds snap to them, 0 to disable (default: 5)",
+            "y_min: Minimum Value Of Y (default: Minimum value of y across the lines)",
+            "y_max: Maximum Value Of Y (default: Maximum value of y across the lines)",
+            "y_style: Style of the Graphs Y Axis ([none, solid*, dotted, dashed])",
+            "y_label: Label of the Y Axis (default: 'Y')",
+            "y_scale: Scale of the Y Axis ([linear*, log10, log2, symlog])",
+            "y_padding: Fraction of the Y range added on each automatic side (default: 0)",
+            "y_include_zero: Extend the automatic Y range to include 0 (default: false)",
+            "y_ticks: Number of rounded ticks on the Y Axis, automatic bounds snap to them, 0 to disable (default: 5)",
+            "y2_min: Minimum Value Of the right Y Axis (default: Minimum value of y across the right lines)",
+            "y2_max: Maximum Value Of the right Y Axis (default: Maximum value of y across the right lines)",
+            "y2_style: Style of the right Y Axis, drawn on the right border ([none, solid*, dotted, dashed])",
+            "y2_label: Label of the right Y Axis (default: 'Y2')",
+            "y2_scale: Scale of the right Y Axis ([linear*, log10, log2, symlog])",
+            "palette: Colors of the lines without their own color ([tableau10*, viridis, magma, none]) (default: tableau10 when there are several lines)",
+            "legend: Show the legend, unlabeled lines included (default: shown when any line has a label)",
+            "legend_position: Position of the legend ([bottom*, right])",
+            "no_header: Don't show header of the graph (default: false)",
+            "no_axis: Don't show axis of the graph (default: false)",
+            "no_borders: Don't show border of the graph (default: false)",
+            "no_figures: Don't show figures of the graph (default: false)",
+            "no_downsample: Draw every point instead of the few per column needed to look the same (default: false)",
+            "lines: [{",
+            &[
+                "\ttype: Type of the Graph Line ([points, lines, steps, bars]) (default: type of the Graph)",
+                "label: Label of the Graph Line shown in the legend (default: nothing)",
+                "color: Color of the Graph Line, a hex string ('#ff8800'), a CSS or ANSI color name or {r: int, g: int, b: int} (0-255) (default: from the palette)",
+                "axis: Y Axis the Graph Line is plotted against ([left*, right])",
+                "points: Points of the Graph Line ([{x: value, y: value}], value being a number, datetime, duration or filesize)",
+                "function: Closure computing y from x ({|x| ...}), evaluated across the X range instead of points (default: nothing)",
+            ]
+            .join("\n\t\t\t"),
+            "}] (default: [], lines from the input are drawn after these)",
+            "annotations: [{",
+            &[
+                "\ttype: Type of the annotation ([hline, vline, band, text])",
+                "x: Position of a vline or a text annotation on the X axis",
+                "y: Position of an hline or a text annotation on the Y axis",
+                "from: Lower Y value of a band",
+                "to: Upper Y value of a band",
+                "label: Text shown next to the annotation, required for text annotations (default: nothing)",
+                "color: Color of the annotation, like the color of a line (default: nothing)",
+                "style: Style of an hline or a vline ([none, solid, dotted, dashed*])",
+            ]
+            .join("\n\t\t\t"),
+            "}] (default: [], drawn over the lines)",
+        ]
+        .join("\n\t\t"),
+        "}",
+    ]
+    .join("\n\t")
+}
@@ -0,0 +1,239 @@
+use nu_ansi_term::Color;
+use nu_plugin::PluginCommand;
+use nu_protocol::{FromValue, LabeledError, Signature, SyntaxShape, Type, Value};
+use rgb::RGB8;
+
+use crate::{
+    plugin::NuGraphsPlugin,
+    stats::unit_of,
+    types::{GraphBar, GraphBarSort, GraphLineColor},
+    util::{pad_visible, visible_width},
+};
+
+use super::{terminal_size, theme};
+
+const PARTIAL_BLOCKS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+pub struct Bar;
+
+impl PluginCommand for Bar {
+    type Plugin = NuGraphsPlugin;
+
+    fn name(&self) -> &str {
+        "graph bar"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name())
+            .input_output_types(vec![
+                (Type::table(), Type::String),
+                (Type::list(Type::Any), Type::String),
+                (Type::Nothing, Type::String),
+            ])
+            .named(
+                "width",
+                SyntaxShape::Int,
+                "Width from the most negative to the largest bar (default: what fits in the terminal next to the labels, 60 when unknown)",
+                Some('w'),
+            )
+            .named(
+                "sort",
+                SyntaxShape::String,
+                "Order of the bars ([none*, asc, desc, label])",
+                Some('s'),
+            )
+            .named(
+                "color",
+                SyntaxShape::Any,
+                "Color of the bars without their own color, a hex string ('#ff8800'), a CSS or ANSI color name or {r: int, g: int, b: int} (0-255)",
+                None,
+            )
+    }
+
+    fn description(&self) -> &str {
+        "Draw a horizontal bar chart of {label: string, value: value, color?: color} rows, negative values extend left of a shared zero baseline"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: nu_protocol::PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::LabeledError> {
+        let width: Option<u32> = call.get_flag("width")?;
+        let sort: Option<GraphBarSort> = call.get_flag("sort")?;
+        let color: Option<GraphLineColor> = call.get_flag("color")?;
+        let colored = theme(engine)?.colored;
+
+        let mut bars = bars_from_input(input.into_value(call.head)?)?;
+
+        match sort.unwrap_or_default() {
+            GraphBarSort::None => {}
+            GraphBarSort::Asc => bars.sort_by(|a, b| a.value.value.total_cmp(&b.value.value)),
+            GraphBarSort::Desc => bars.sort_by(|a, b| b.value.value.total_cmp(&a.value.value)),
+            GraphBarSort::Label => bars.sort_by(|a, b| a.label.cmp(&b.label)),
+        }
+
+        let columns = match width {
+            Some(_) => None,
+            None => terminal_size(engine)?.map(|(columns, _)| columns),
+        };
+        let chart = draw(&bars, width, columns, color, colored)?;
+
+        Ok(nu_protocol::PipelineData::Value(
+            Value::string(chart, call.head),
+            None,
+        ))
+    }
+}
+
+fn bars_from_input(value: Value) -> Result<Vec<GraphBar>, LabeledError> {
+    match value {
+        Value::Nothing { .. } => Ok(vec![]),
+        value => Ok(Vec::<GraphBar>::from_value(value)?),
+    }
+}
+
+fn draw(
+    bars: &[GraphBar],
+    width: Option<u32>,
+    columns: Option<u32>,
+    color: Option<GraphLineColor>,
+    colored: bool,
+) -> Result<String, LabeledError> {
+    let unit = unit_of(&bars.iter().map(|bar| bar.value).collect::<Vec<_>>())?;
+    let (min, max) = bars
+        .iter()
+        .map(|bar| bar.value.value)
+        .filter(|value| value.is_finite())
+        .fold((0.0, 0.0), |(min, max), value| {
+            (f64::min(min, value), f64::max(max, value))
+        });
+    let span = max - min;
+    let values = bars
+        .iter()
+        .map(|bar| unit.format(bar.value.value, span))
+        .collect::<Vec<_>>();
+    let label_width = bars
+        .iter()
+        .map(|bar| visible_width(&bar.label))
+        .max()
+        .unwrap_or_default();
+    let value_width = values
+        .iter()
+        .map(|value| visible_width(value))
+        .max()
+        .unwrap_or_default();
+
+    let width = match (width, columns) {
+        (Some(width), _) => width,
+        (None, Some(columns)) => columns
+            .saturating_sub((label_width + value_width) as u32 + 2)
+            .max(1),
+        (None, None) => 60,
+    } as f64;
+
+    // bars share a zero baseline, negative ones grow to the left of it in half cells since
+    // there are no right aligned partial blocks finer than that
+    let length = |value: f64| match span > 0.0 && value.is_finite() {
+        true => value.abs() / span * width,
+        false => 0.0,
+    };
+    let baseline = length(min).ceil() as usize;
+
+    Ok(bars
+        .iter()
+        .zip(&values)
+        .map(
+            |(
+                GraphBar {
+                    label,
+                    value,
+                    color: bar_color,
+                },
+                formatted,
+            )| {
+                let bar = match value.value < 0.0 {
+                    true => {
+                        let halves = (length(value.value) * 2.0).round() as usize;
+                        let half = match halves % 2 {
+                            1 => "▐",
+                            _ => "",
+                        };
+                        let bar = format!("{half}{}", "█".repeat(halves / 2));
+                        format!("{bar:>baseline$}")
+                    }
+                    false => {
+                        let eighths = (length(value.value) * 8.0).round() as usize;
+                        format!(
+                            "{}{}{}",
+                            " ".repeat(baseline),
+                            "█".repeat(eighths / 8),
+                            PARTIAL_BLOCKS[eighths % 8]
+                        )
+                    }
+                };
+                let bar = match bar_color.or(color).filter(|_| colored) {
+                    Some(color) => {
+                        let RGB8 { r, g, b } = color.into();
+                        Color::Rgb(r, g, b).paint(bar).to_string()
+                    }
+                    None => bar,
+                };
+
+                format!("{} {bar} {formatted}", pad_visible(label, label_width))
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::GraphValue;
+
+    use super::*;
+
+    fn bars(values: &[(&str, f64)]) -> Vec<GraphBar> {
+        values
+            .iter()
+            .map(|(label, value)| GraphBar {
+                label: label.to_string(),
+                value: GraphValue::number(*value),
+                color: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn draws_negative_bars_left_of_a_zero_baseline() {
+        let chart = draw(
+            &bars(&[("up", 3.0), ("down", -1.0), ("none", 0.0)]),
+            Some(8),
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        assert_eq!(chart, "up     ██████ 3\ndown ██ -1\nnone    0");
+
+        let chart = draw(
+            &bars(&[("a", 3.0), ("b", -1.0)]),
+            Some(6),
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        assert_eq!(chart, "a   ████▌ 3\nb ▐█ -1");
+    }
+
+    #[test]
+    fn draws_nothing_without_bars() {
+        for input in [Value::test_nothing(), Value::test_list(vec![])] {
+            let bars = bars_from_input(input).unwrap();
+            assert_eq!(draw(&bars, None, None, None, false).unwrap(), "");
+        }
+    }
+}
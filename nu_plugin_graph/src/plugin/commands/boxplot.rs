@@ -0,0 +1,299 @@
+use nu_ansi_term::Color;
+use nu_plugin::PluginCommand;
+use nu_protocol::{IntoValue, LabeledError, Signature, Spanned, SyntaxShape, Type, Value};
+use rgb::RGB8;
+
+use crate::{
+    axis::{Axis, AxisOptions, DEFAULT_TICKS},
+    input::{InputColumns, series_from_input},
+    plugin::NuGraphsPlugin,
+    stats::box_stats,
+    types::{GraphBoxStats, GraphLineColor, GraphScale, GraphValue},
+    util::{pad_visible, visible_width},
+};
+
+use super::{Scale, get_all_flags, theme};
+
+const HORIZONTAL_LENGTH: usize = 60;
+const VERTICAL_LENGTH: usize = 20;
+const BOX_WIDTH: usize = 3;
+
+pub struct BoxPlot;
+
+impl PluginCommand for BoxPlot {
+    type Plugin = NuGraphsPlugin;
+
+    fn name(&self) -> &str {
+        "graph box"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name())
+            .input_output_types(vec![
+                (Type::list(Type::Any), Type::String),
+                (Type::table(), Type::String),
+                (Type::list(Type::Any), Type::table()),
+                (Type::table(), Type::table()),
+            ])
+            .named(
+                "y",
+                SyntaxShape::OneOf(vec![
+                    SyntaxShape::String,
+                    SyntaxShape::List(Box::new(SyntaxShape::String)),
+                ]),
+                "Column(s) of the input to summarize, one box each, can be repeated (default: all numeric columns)",
+                None,
+            )
+            .named(
+                "group-by",
+                SyntaxShape::String,
+                "Column of the input to split the values by, one box per distinct value",
+                None,
+            )
+            .named(
+                "length",
+                SyntaxShape::Int,
+                "Length of the value axis in characters (default: 60, 20 when vertical)",
+                Some('l'),
+            )
+            .switch("vertical", "Draw the boxes vertically", Some('v'))
+            .switch(
+                "table",
+                "Return the statistics as a table of {label, count, min, lower_whisker, q1, median, mean, q3, upper_whisker, max, outliers} instead of drawing them",
+                Some('t'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        "Draw a box plot of the median, quartiles, whiskers (1.5 IQR) and outliers of one or more series"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: nu_protocol::PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::LabeledError> {
+        let columns = InputColumns {
+            y: get_all_flags(call, "y")?,
+            group_by: call.get_flag("group-by")?,
+            ..Default::default()
+        };
+        let vertical = call.has_flag("vertical")?;
+        let length: Option<Spanned<i64>> = call.get_flag("length")?;
+
+        let length = match length {
+            Some(Spanned { item, .. }) if item >= 2 => item as usize,
+            Some(Spanned { span, .. }) => {
+                return Err(LabeledError::new("Invalid length")
+                    .with_label("length should be at least 2", span));
+            }
+            None if vertical => VERTICAL_LENGTH,
+            None => HORIZONTAL_LENGTH,
+        };
+
+        let series = series_from_input(input, &columns, call.head)?;
+        let values = series
+            .iter()
+            .flat_map(|(_, values)| values.iter().copied())
+            .collect::<Vec<_>>();
+        let stats = series
+            .into_iter()
+            .map(|(label, values)| {
+                box_stats(label.clone(), &values)?.ok_or_else(|| {
+                    LabeledError::new("No values to summarize")
+                        .with_label(format!("'{label}' has no finite values"), call.head)
+                })
+            })
+            .collect::<Result<Vec<_>, LabeledError>>()?;
+
+        if call.has_flag("table")? {
+            return Ok(nu_protocol::PipelineData::Value(
+                stats.into_value(call.head),
+                None,
+            ));
+        }
+
+        let axis = Axis::infer("value", values, None, None, GraphScale::Linear)?;
+        let range = axis.range(
+            axis.data_range(),
+            AxisOptions {
+                padding: 0.0,
+                include_zero: false,
+                ticks: DEFAULT_TICKS,
+            },
+        );
+        let scale = Scale {
+            axis,
+            range,
+            length,
+        };
+
+        let colored = theme(engine)?.colored;
+        let boxes = stats
+            .iter()
+            .enumerate()
+            .map(|(i, stats)| {
+                let cells = box_cells(stats, &scale)
+                    .into_iter()
+                    .map(|cell| {
+                        let cell = match vertical {
+                            true => vertical_cell(cell),
+                            false => cell.to_string(),
+                        };
+
+                        match (colored, cell.trim().is_empty()) {
+                            (true, false) => paint(&cell, GraphLineColor::from_palette(i)),
+                            _ => cell,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                (stats.label.as_str(), cells)
+            })
+            .collect::<Vec<_>>();
+
+        let chart = match vertical {
+            true => draw_vertical(&boxes, &scale),
+            false => draw_horizontal(&boxes, &scale),
+        };
+
+        Ok(nu_protocol::PipelineData::Value(
+            Value::string(chart, call.head),
+            None,
+        ))
+    }
+}
+
+fn box_cells(stats: &GraphBoxStats, scale: &Scale) -> Vec<char> {
+    let position = |value: &GraphValue| scale.value_position(value.value);
+    let (lower, q1, median, q3, upper) = (
+        position(&stats.lower_whisker),
+        position(&stats.q1),
+        position(&stats.median),
+        position(&stats.q3),
+        position(&stats.upper_whisker),
+    );
+
+    let mut cells = vec![' '; scale.length];
+    cells[lower..=upper].fill('─');
+    cells[lower] = '├';
+    cells[upper] = '┤';
+    cells[q1..=q3].fill('▒');
+    cells[median] = '█';
+    for outlier in &stats.outliers {
+        cells[position(outlier)] = '•';
+    }
+
+    cells
+}
+
+fn vertical_cell(cell: char) -> String {
+    match cell {
+        '─' => " │ ".into(),
+        '├' => " ┴ ".into(),
+        '┤' => " ┬ ".into(),
+        '•' => " • ".into(),
+        ' ' => " ".repeat(BOX_WIDTH),
+        cell => cell.to_string().repeat(BOX_WIDTH),
+    }
+}
+
+fn paint(cell: &str, color: GraphLineColor) -> String {
+    let RGB8 { r, g, b } = color.into();
+    Color::Rgb(r, g, b).paint(cell).to_string()
+}
+
+fn draw_horizontal(boxes: &[(&str, Vec<String>)], scale: &Scale) -> String {
+    let label_width = boxes
+        .iter()
+        .map(|(label, _)| visible_width(label))
+        .max()
+        .unwrap_or_default();
+
+    let mut axis = vec![' '; scale.length];
+    let mut placed = vec![];
+    for (position, label) in scale.ticks() {
+        let label = label.chars().collect::<Vec<_>>();
+        let start = position
+            .saturating_sub(label.len() / 2)
+            .min(scale.length.saturating_sub(label.len()));
+        let end = start + label.len();
+
+        if !placed
+            .iter()
+            .any(|(other_start, other_end)| start <= *other_end && *other_start <= end)
+        {
+            if axis.len() < end {
+                axis.resize(end, ' ');
+            }
+            axis[start..end].copy_from_slice(&label);
+            placed.push((start, end));
+        }
+    }
+
+    boxes
+        .iter()
+        .map(|(label, cells)| {
+            format!(
+                "{} {}",
+                pad_visible(label, label_width),
+                cells.concat().trim_end()
+            )
+        })
+        .chain([format!(
+            "{} {}",
+            " ".repeat(label_width),
+            axis.into_iter().collect::<String>().trim_end()
+        )])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn draw_vertical(boxes: &[(&str, Vec<String>)], scale: &Scale) -> String {
+    let ticks = scale.ticks();
+    let tick_width = ticks
+        .iter()
+        .map(|(_, label)| visible_width(label))
+        .max()
+        .unwrap_or_default();
+    let column_width = boxes
+        .iter()
+        .map(|(label, _)| visible_width(label))
+        .chain([BOX_WIDTH])
+        .max()
+        .unwrap_or_default();
+    let center = |s: &str| {
+        let indent = column_width.saturating_sub(visible_width(s)) / 2;
+        pad_visible(&format!("{}{s}", " ".repeat(indent)), column_width)
+    };
+
+    (0..scale.length)
+        .rev()
+        .map(|row| {
+            let tick = ticks
+                .iter()
+                .find(|(position, _)| *position == row)
+                .map(|(_, label)| label.as_str())
+                .unwrap_or_default();
+            let cells = boxes
+                .iter()
+                .map(|(_, cells)| center(&cells[row]))
+                .collect::<Vec<_>>();
+
+            format!("{tick:>tick_width$} {}", cells.join(" "))
+        })
+        .chain([format!(
+            "{} {}",
+            " ".repeat(tick_width),
+            boxes
+                .iter()
+                .map(|(label, _)| center(label))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )])
+        .map(|line| line.trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
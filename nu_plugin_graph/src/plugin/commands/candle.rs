@@ -0,0 +1,386 @@
+use nu_ansi_term::Color;
+use nu_plugin::PluginCommand;
+use nu_protocol::{Config, LabeledError, Signature, Spanned, SyntaxShape, Type, Value};
+
+use crate::{
+    axis::{Axis, AxisOptions, DEFAULT_TICKS},
+    input::{Candle, CandleColumns, candles_from_input},
+    plugin::NuGraphsPlugin,
+    types::{GraphScale, GraphValue},
+};
+
+use super::{Scale, theme};
+
+const HEIGHT: usize = 20;
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const UP: Color = Color::Green;
+const DOWN: Color = Color::Red;
+
+pub struct Candlestick;
+
+impl PluginCommand for Candlestick {
+    type Plugin = NuGraphsPlugin;
+
+    fn name(&self) -> &str {
+        "graph candle"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name())
+            .input_output_types(vec![
+                (Type::list(Type::Any), Type::String),
+                (Type::table(), Type::String),
+            ])
+            .named(
+                "x",
+                SyntaxShape::String,
+                "Column of the input labeling each candle (default: 'x', row index when missing)",
+                None,
+            )
+            .named(
+                "open",
+                SyntaxShape::String,
+                "Column of the input with the opening value (default: 'open')",
+                None,
+            )
+            .named(
+                "high",
+                SyntaxShape::String,
+                "Column of the input with the highest value (default: 'high')",
+                None,
+            )
+            .named(
+                "low",
+                SyntaxShape::String,
+                "Column of the input with the lowest value (default: 'low')",
+                None,
+            )
+            .named(
+                "close",
+                SyntaxShape::String,
+                "Column of the input with the closing value (default: 'close')",
+                None,
+            )
+            .named(
+                "volume",
+                SyntaxShape::String,
+                "Column of the input with the volume drawn as bars beneath (default: 'volume' when present)",
+                None,
+            )
+            .named(
+                "height",
+                SyntaxShape::Int,
+                "Height of the value axis in characters (default: 20)",
+                None,
+            )
+            .switch("no-volume", "Don't draw the volume bars", None)
+    }
+
+    fn description(&self) -> &str {
+        "Draw a candlestick chart of open, high, low and close values, with optional volume bars"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: nu_protocol::PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::LabeledError> {
+        let columns = CandleColumns {
+            x: call.get_flag("x")?,
+            open: call.get_flag("open")?,
+            high: call.get_flag("high")?,
+            low: call.get_flag("low")?,
+            close: call.get_flag("close")?,
+            volume: call.get_flag("volume")?,
+        };
+        let height: Option<Spanned<i64>> = call.get_flag("height")?;
+        let no_volume = call.has_flag("no-volume")?;
+
+        let height = match height {
+            Some(Spanned { item, .. }) if item >= 3 => item as usize,
+            Some(Spanned { span, .. }) => {
+                return Err(LabeledError::new("Invalid height")
+                    .with_label("height should be at least 3", span));
+            }
+            None => HEIGHT,
+        };
+
+        let candles = candles_from_input(input, &columns, call.head)?;
+        if candles.is_empty() {
+            return Err(LabeledError::new("No candles to draw").with_label(
+                "input has no rows with open, high, low and close",
+                call.head,
+            ));
+        }
+
+        let colored = theme(engine)?.colored;
+        let chart = draw(&candles, height, no_volume, colored)?;
+
+        Ok(nu_protocol::PipelineData::Value(
+            Value::string(chart, call.head),
+            None,
+        ))
+    }
+}
+
+fn draw(
+    candles: &[Candle],
+    height: usize,
+    no_volume: bool,
+    colored: bool,
+) -> Result<String, LabeledError> {
+    let axis = Axis::infer(
+        "value",
+        candles
+            .iter()
+            .flat_map(|candle| [candle.open, candle.high, candle.low, candle.close]),
+        None,
+        None,
+        GraphScale::Linear,
+    )?;
+    let range = axis.range(
+        axis.data_range(),
+        AxisOptions {
+            padding: 0.0,
+            include_zero: false,
+            ticks: DEFAULT_TICKS,
+        },
+    );
+    let scale = Scale {
+        axis,
+        range,
+        length: height,
+    };
+
+    let volumes = candles
+        .iter()
+        .filter_map(|candle| candle.volume)
+        .collect::<Vec<_>>();
+    let volume_height = match no_volume || volumes.is_empty() {
+        true => 0,
+        false => (height / 4).max(2),
+    };
+    let volume_axis = Axis::infer("volume", volumes, None, None, GraphScale::Linear)?;
+
+    let paint = |cell: char, candle: &Candle| match colored && cell != ' ' {
+        true => {
+            let color = match candle.close.value >= candle.open.value {
+                true => UP,
+                false => DOWN,
+            };
+            color.paint(cell.to_string()).to_string()
+        }
+        false => cell.to_string(),
+    };
+
+    let columns = candles
+        .iter()
+        .map(|candle| {
+            let mut cells = candle_cells(candle, &scale, colored);
+            cells.extend(volume_cells(candle, &volume_axis, volume_height));
+            cells
+                .into_iter()
+                .map(|cell| paint(cell, candle))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut labels = vec![String::new(); height + volume_height];
+    for (position, label) in scale.ticks() {
+        labels[height - 1 - position] = label;
+    }
+    if let Some((_, max)) = volume_axis.data_range().filter(|_| volume_height > 0) {
+        labels[height] = volume_axis.format(max);
+    }
+    let label_width = labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    Ok(labels
+        .iter()
+        .enumerate()
+        .map(|(row, label)| {
+            let cells = columns
+                .iter()
+                .map(|cells| cells[row].as_str())
+                .collect::<Vec<_>>();
+            format!("{label:>label_width$} {}", cells.join(" "))
+                .trim_end()
+                .to_string()
+        })
+        .chain([format!("{} {}", " ".repeat(label_width), x_labels(candles))])
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn candle_cells(candle: &Candle, scale: &Scale, colored: bool) -> Vec<char> {
+    let position = |value: &GraphValue| scale.value_position(value.value);
+    let (open, close) = (position(&candle.open), position(&candle.close));
+    let low = position(&candle.low).min(open).min(close);
+    let high = position(&candle.high).max(open).max(close);
+
+    let body = match (colored, candle.close.value >= candle.open.value) {
+        (false, true) => '▒',
+        _ => '█',
+    };
+
+    let mut cells = vec![' '; scale.length];
+    cells[low..=high].fill('│');
+    match candle.open.value == candle.close.value {
+        true => cells[open] = '┿',
+        false => cells[open.min(close)..=open.max(close)].fill(body),
+    }
+
+    cells.reverse();
+    cells
+}
+
+fn volume_cells(candle: &Candle, axis: &Axis, height: usize) -> Vec<char> {
+    let eighths = match (candle.volume, axis.data_range()) {
+        (Some(volume), Some((_, max))) if max > 0.0 => {
+            let fraction = (axis.project(volume.value) / max).clamp(0.0, 1.0);
+            (fraction * (height * BLOCKS.len()) as f32).round() as usize
+        }
+        _ => 0,
+    };
+
+    (0..height)
+        .rev()
+        .map(|row| match eighths.saturating_sub(row * BLOCKS.len()) {
+            0 => ' ',
+            level => BLOCKS[level.min(BLOCKS.len()) - 1],
+        })
+        .collect()
+}
+
+fn x_labels(candles: &[Candle]) -> String {
+    let values = candles
+        .iter()
+        .map(|candle| GraphValue::try_from_value(&candle.x).ok().flatten())
+        .collect::<Option<Vec<_>>>();
+    let labels = match values {
+        Some(values) => {
+            let axis = Axis::infer("x", values.clone(), None, None, GraphScale::Linear).ok();
+            values
+                .iter()
+                .map(|value| match &axis {
+                    Some(axis) => axis.format(axis.project(value.value)),
+                    None => value.value.to_string(),
+                })
+                .collect::<Vec<_>>()
+        }
+        None => candles
+            .iter()
+            .map(|candle| candle.x.to_expanded_string(", ", &Config::default()))
+            .collect(),
+    };
+
+    let mut line = vec![' '; candles.len() * 2];
+    let mut placed = vec![];
+    let last = labels.len().saturating_sub(1);
+    let order = [0, last].into_iter().chain(1..last);
+
+    for i in order {
+        let label = labels[i].chars().collect::<Vec<_>>();
+        let start = match i == last && i > 0 {
+            true => (i * 2 + 1).saturating_sub(label.len()),
+            false => i * 2,
+        };
+        let end = start + label.len();
+
+        if placed
+            .iter()
+            .any(|(other_start, other_end)| start <= *other_end && *other_start <= end)
+        {
+            continue;
+        }
+
+        if line.len() < end {
+            line.resize(end, ' ');
+        }
+        line[start..end].copy_from_slice(&label);
+        placed.push((start, end));
+    }
+
+    line.into_iter().collect::<String>().trim_end().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use nu_protocol::{PipelineData, Span, record};
+
+    use crate::input::candles_from_input;
+
+    use super::*;
+
+    fn candles(volume: bool) -> Vec<Candle> {
+        let row = |x: &str, [open, high, low, close]: [i64; 4], v: i64| {
+            let mut row = record! {
+                "x" => Value::test_string(x),
+                "open" => Value::test_int(open),
+                "high" => Value::test_int(high),
+                "low" => Value::test_int(low),
+                "close" => Value::test_int(close),
+            };
+            if volume {
+                row.insert("volume", Value::test_int(v));
+            }
+            Value::test_record(row)
+        };
+        let input = Value::test_list(vec![
+            row("mon", [10, 14, 8, 13], 100),
+            row("tue", [13, 15, 9, 10], 50),
+            row("wed", [10, 10, 10, 10], 25),
+        ]);
+
+        candles_from_input(
+            PipelineData::Value(input, None),
+            &CandleColumns::default(),
+            Span::test_data(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn draws_bodies_wicks_and_volume() {
+        assert_eq!(
+            draw(&candles(true), 8, false, false).unwrap(),
+            [
+                " 16",
+                "      │",
+                " 14 │ │",
+                " 12 ▒ █",
+                "    ▒ █",
+                " 10 ▒ █ ┿",
+                "    │ │",
+                "  8 │",
+                "100 █",
+                "    █ █ ▄",
+                "    mon",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn leaves_out_volume_when_missing_or_hidden() {
+        let without_volume = draw(&candles(false), 8, false, false).unwrap();
+        assert_eq!(without_volume.lines().count(), 9);
+        assert!(without_volume.starts_with("16\n     │\n14 │ │"));
+        assert_eq!(
+            draw(&candles(true), 8, true, false).unwrap(),
+            without_volume
+        );
+    }
+
+    #[test]
+    fn colors_candles_by_direction() {
+        let chart = draw(&candles(false), 8, false, true).unwrap();
+        let row = chart.lines().nth(3).unwrap();
+        assert_eq!(row, format!("12 {} {}", UP.paint("█"), DOWN.paint("█")));
+    }
+}
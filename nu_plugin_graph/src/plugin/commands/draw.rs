@@ -0,0 +1,114 @@
+use nu_plugin::PluginCommand;
+use nu_protocol::{Signature, SyntaxShape, Type};
+
+use crate::{
+    chart::render,
+    input::{InputColumns, InputData, lines_from_input},
+    plugin::NuGraphsPlugin,
+    types::{GraphConfig, GraphFormat},
+};
+
+use super::{eval_functions, fit_to_terminal, get_all_flags, graph_config_description, theme};
+
+pub struct Draw;
+
+impl PluginCommand for Draw {
+    type Plugin = NuGraphsPlugin;
+
+    fn name(&self) -> &str {
+        "draw"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name())
+            .input_output_types(vec![
+                (Type::Nothing, Type::String),
+                (Type::table(), Type::String),
+                (Type::list(Type::Any), Type::String),
+                (Type::Nothing, Type::Binary),
+                (Type::table(), Type::Binary),
+                (Type::list(Type::Any), Type::Binary),
+            ])
+            .named(
+                "x",
+                SyntaxShape::String,
+                "Column of the input to use for X (default: row index)",
+                None,
+            )
+            .named(
+                "y",
+                SyntaxShape::OneOf(vec![
+                    SyntaxShape::String,
+                    SyntaxShape::List(Box::new(SyntaxShape::String)),
+                ]),
+                "Column(s) of the input to use for Y, one line each, can be repeated (default: all numeric columns)",
+                None,
+            )
+            .named(
+                "group-by",
+                SyntaxShape::String,
+                "Column of the input to split the lines by, one colored line per distinct value",
+                None,
+            )
+            .named(
+                "format",
+                SyntaxShape::String,
+                "Output format of the chart ([text*, svg, png, sixel, kitty])",
+                Some('f'),
+            )
+            .switch(
+                "no-downsample",
+                "Draw every point instead of the few per column needed to look the same",
+                None,
+            )
+            .optional(
+                "graph-config",
+                SyntaxShape::Any,
+                graph_config_description(),
+            )
+    }
+
+    fn description(&self) -> &str {
+        "Draw a chart"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: nu_protocol::PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::LabeledError> {
+        let mut config: GraphConfig = call.opt(0)?.unwrap_or_default();
+        let format: Option<GraphFormat> = call.get_flag("format")?;
+
+        if call.has_flag("no-downsample")? {
+            config.no_downsample = Some(true);
+        }
+
+        let columns = InputColumns {
+            x: call.get_flag("x")?,
+            y: get_all_flags(call, "y")?,
+            group_by: call.get_flag("group-by")?,
+        };
+        let InputData {
+            x_label,
+            y_label,
+            lines,
+            ..
+        } = lines_from_input(input, &columns, call.head)?;
+
+        config.x_label = config.x_label.or(x_label);
+        config.y_label = config.y_label.or(y_label);
+        config.lines.extend(lines);
+
+        let format = format.unwrap_or_default();
+        fit_to_terminal(engine, &mut config, format)?;
+        eval_functions(engine, &mut config, call.head)?;
+
+        Ok(nu_protocol::PipelineData::Value(
+            render(config, format, theme(engine)?, call.head)?,
+            None,
+        ))
+    }
+}
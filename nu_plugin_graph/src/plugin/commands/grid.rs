@@ -0,0 +1,346 @@
+use nu_plugin::PluginCommand;
+use nu_protocol::{FromValue, LabeledError, Signature, Spanned, SyntaxShape, Type, Value};
+
+use crate::{
+    chart::{Plot, render},
+    plugin::NuGraphsPlugin,
+    theme::Theme,
+    types::{GraphConfig, GraphFormat, GraphValue},
+    util::{pad_visible, side_by_side, visible_width},
+};
+
+use super::{eval_functions, fit_to_area, graph_config_description, terminal_size, theme};
+
+const GAP: u32 = 2;
+
+struct Panel {
+    title: Option<String>,
+    config: GraphConfig,
+}
+
+pub struct Grid;
+
+impl PluginCommand for Grid {
+    type Plugin = NuGraphsPlugin;
+
+    fn name(&self) -> &str {
+        "graph grid"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name())
+            .input_output_types(vec![(Type::list(Type::Any), Type::String)])
+            .named(
+                "columns",
+                SyntaxShape::Int,
+                "Number of panels per row (default: square root of the number of panels, rounded up)",
+                Some('c'),
+            )
+            .switch(
+                "share-x",
+                "Draw every panel with the same X range",
+                None,
+            )
+            .switch(
+                "share-y",
+                "Draw every panel with the same Y range",
+                None,
+            )
+            .optional(
+                "graph-config",
+                SyntaxShape::Any,
+                format!(
+                    "Defaults for every panel, the panels of the input override them\n\t\t{}",
+                    graph_config_description()
+                ),
+            )
+    }
+
+    fn description(&self) -> &str {
+        "Draw a grid of charts from a list of graph configs, each with an optional title"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: nu_protocol::PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::LabeledError> {
+        let defaults: Option<Value> = call.opt(0)?;
+        let columns: Option<Spanned<i64>> = call.get_flag("columns")?;
+
+        let mut panels = match input.into_value(call.head)? {
+            Value::List { vals, .. } => vals
+                .into_iter()
+                .map(|panel| panel_from_value(panel, defaults.as_ref()))
+                .collect::<Result<Vec<_>, LabeledError>>()?,
+            value => {
+                return Err(LabeledError::new("Unsupported input").with_label(
+                    format!("expected a list of graph configs, got {}", value.get_type()),
+                    value.span(),
+                ));
+            }
+        };
+
+        if panels.is_empty() {
+            return Err(LabeledError::new("No panels to draw")
+                .with_label("expected at least one graph config", call.head));
+        }
+
+        let columns = match columns {
+            Some(Spanned { item, .. }) if item > 0 => (item as usize).min(panels.len()),
+            Some(Spanned { span, .. }) => {
+                return Err(LabeledError::new("Invalid number of columns")
+                    .with_label("columns should be at least 1", span));
+            }
+            None => (panels.len() as f64).sqrt().ceil() as usize,
+        };
+        let rows = panels.len().div_ceil(columns);
+
+        if let Some((terminal_columns, terminal_rows)) = terminal_size(engine)? {
+            let panel_columns =
+                terminal_columns.saturating_sub(GAP * (columns as u32 - 1)) / columns as u32;
+            let panel_rows = terminal_rows.saturating_sub(rows as u32 - 1) / rows as u32;
+
+            for Panel { title, config } in &mut panels {
+                let title_rows = title.is_some() as u32;
+                fit_to_area(config, panel_columns, panel_rows.saturating_sub(title_rows));
+            }
+        }
+
+        for Panel { config, .. } in &mut panels {
+            eval_functions(engine, config, call.head)?;
+        }
+
+        let share_x = call.has_flag("share-x")?;
+        let share_y = call.has_flag("share-y")?;
+        if share_x || share_y {
+            share_axes(&mut panels, share_x, share_y)?;
+        }
+
+        let theme = theme(engine)?;
+        let rendered = panels
+            .into_iter()
+            .map(|Panel { title, config }| {
+                let chart =
+                    render(config, GraphFormat::Text, theme.clone(), call.head)?.into_string()?;
+
+                Ok(with_title(chart, title, &theme))
+            })
+            .collect::<Result<Vec<_>, LabeledError>>()?;
+
+        Ok(nu_protocol::PipelineData::Value(
+            Value::string(arrange(&rendered, columns), call.head),
+            None,
+        ))
+    }
+}
+
+fn panel_from_value(panel: Value, defaults: Option<&Value>) -> Result<Panel, LabeledError> {
+    let span = panel.span();
+    let Value::Record { val, .. } = panel else {
+        return Err(LabeledError::new("Unsupported panel").with_label(
+            format!("expected a graph config record, got {}", panel.get_type()),
+            span,
+        ));
+    };
+
+    let mut record = match defaults {
+        Some(Value::Record { val: defaults, .. }) => defaults.clone().into_owned(),
+        Some(defaults) => {
+            return Err(LabeledError::new("Unsupported graph config").with_label(
+                format!("expected a record, got {}", defaults.get_type()),
+                defaults.span(),
+            ));
+        }
+        None => Default::default(),
+    };
+    for (column, value) in val.into_owned() {
+        record.insert(column, value);
+    }
+
+    let title = record.remove("title").map(String::from_value).transpose()?;
+    let config = GraphConfig::from_value(Value::record(record, span))?;
+
+    Ok(Panel { title, config })
+}
+
+fn share_axes(panels: &mut [Panel], share_x: bool, share_y: bool) -> Result<(), LabeledError> {
+    let plots = panels
+        .iter()
+        .map(|Panel { config, .. }| Plot::new(config.clone(), Theme::default()))
+        .collect::<Result<Vec<_>, LabeledError>>()?;
+
+    let union = |bounds: Vec<(GraphValue, GraphValue)>| {
+        bounds
+            .into_iter()
+            .reduce(|(min, max), (other_min, other_max)| {
+                (
+                    match other_min.value < min.value {
+                        true => other_min,
+                        false => min,
+                    },
+                    match other_max.value > max.value {
+                        true => other_max,
+                        false => max,
+                    },
+                )
+            })
+    };
+
+    let x_bounds = union(
+        plots
+            .iter()
+            .map(|plot| {
+                (
+                    plot.x_axis.value(plot.x_range.0),
+                    plot.x_axis.value(plot.x_range.1),
+                )
+            })
+            .collect(),
+    );
+    let y_bounds = union(
+        plots
+            .iter()
+            .map(|plot| {
+                (
+                    plot.y_axis.value(plot.y_range.0),
+                    plot.y_axis.value(plot.y_range.1),
+                )
+            })
+            .collect(),
+    );
+
+    for Panel { config, .. } in panels {
+        if let (true, Some((min, max))) = (share_x, x_bounds) {
+            config.x_min = Some(min);
+            config.x_max = Some(max);
+        }
+        if let (true, Some((min, max))) = (share_y, y_bounds) {
+            config.y_min = Some(min);
+            config.y_max = Some(max);
+        }
+    }
+
+    Ok(())
+}
+
+fn arrange(charts: &[String], columns: usize) -> String {
+    charts
+        .chunks(columns)
+        .map(|row| {
+            row.iter().skip(1).fold(row[0].clone(), |left, right| {
+                let right = right.lines().map(String::from).collect::<Vec<_>>();
+                side_by_side(&left, &right, GAP as usize)
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn with_title(chart: String, title: Option<String>, theme: &Theme) -> String {
+    let Some(title) = title else {
+        return chart;
+    };
+
+    let width = chart.lines().map(visible_width).max().unwrap_or_default();
+    let indent = width.saturating_sub(visible_width(&title)) / 2;
+    let title = pad_visible(&format!("{}{title}", " ".repeat(indent)), width);
+
+    match theme.colored {
+        true => format!("{}\n{chart}", theme.label.bold().paint(title)),
+        false => format!("{title}\n{chart}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nu_protocol::record;
+
+    use super::*;
+
+    fn line(from: f64, to: f64) -> Value {
+        Value::test_list(
+            [from, to]
+                .into_iter()
+                .enumerate()
+                .map(|(x, y)| {
+                    Value::test_record(record! {
+                        "x" => Value::test_float(x as f64),
+                        "y" => Value::test_float(y),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn panel(title: Option<&str>, from: f64, to: f64) -> Value {
+        let mut panel = record! {
+            "lines" => Value::test_list(vec![Value::test_record(record! { "points" => line(from, to) })]),
+        };
+        if let Some(title) = title {
+            panel.insert("title", Value::test_string(title));
+        }
+
+        Value::test_record(panel)
+    }
+
+    #[test]
+    fn merges_each_panel_over_the_defaults() {
+        let defaults = Value::test_record(record! {
+            "title" => Value::test_string("default"),
+            "width" => Value::test_int(20),
+            "no_borders" => Value::test_bool(true),
+        });
+
+        let Panel { title, config } =
+            panel_from_value(panel(Some("cpu"), 0.0, 1.0), Some(&defaults)).unwrap();
+        assert_eq!(title.as_deref(), Some("cpu"));
+        assert_eq!(config.no_borders, Some(true));
+        assert_eq!(config.lines[0].points.len(), 2);
+
+        let Panel { title, .. } = panel_from_value(panel(None, 0.0, 1.0), Some(&defaults)).unwrap();
+        assert_eq!(title.as_deref(), Some("default"));
+
+        let error = panel_from_value(Value::test_int(1), None).err().unwrap();
+        assert_eq!(error.msg, "Unsupported panel");
+    }
+
+    #[test]
+    fn shares_the_union_of_the_ranges() {
+        let mut panels = [panel(None, 0.0, 1.0), panel(None, -5.0, 3.0)]
+            .into_iter()
+            .map(|panel| panel_from_value(panel, None))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        share_axes(&mut panels, false, true).unwrap();
+
+        for Panel { config, .. } in &panels {
+            assert_eq!(config.x_min.map(|x| x.value), None);
+            let plot = Plot::new(config.clone(), Theme::default()).unwrap();
+            assert!(plot.y_range.0 <= -5.0 && plot.y_range.1 >= 3.0);
+        }
+        assert_eq!(
+            panels[0].config.y_min.map(|y| y.value),
+            panels[1].config.y_min.map(|y| y.value)
+        );
+    }
+
+    #[test]
+    fn arranges_titled_charts_in_rows() {
+        let theme = Theme {
+            colored: false,
+            ..Default::default()
+        };
+        let charts = ["ab\ncd", "efg", "h"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, chart)| with_title(chart.into(), (i == 0).then(|| "t".into()), &theme))
+            .collect::<Vec<_>>();
+
+        assert_eq!(charts[0], "t \nab\ncd");
+        assert_eq!(arrange(&charts, 2), "t   efg\nab\ncd\n\nh");
+        assert_eq!(arrange(&charts, 3), "t   efg  h\nab\ncd");
+    }
+}
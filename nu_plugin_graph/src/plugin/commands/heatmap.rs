@@ -0,0 +1,229 @@
+use nu_ansi_term::{Color, Style};
+use nu_plugin::PluginCommand;
+use nu_protocol::{Signature, Spanned, SyntaxShape, Type, Value};
+use rgb::RGB8;
+
+use crate::{
+    input::{HeatmapColumns, HeatmapData, heatmap_from_input},
+    plugin::NuGraphsPlugin,
+    stats::{sorted_finite, unit_of},
+    types::{GraphGradient, GraphGradientPreset, GraphLineColor, GraphValue},
+    util::{pad_visible, visible_width},
+};
+
+use super::theme;
+
+const SCALE_WIDTH: usize = 24;
+const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+
+pub struct Heatmap;
+
+impl PluginCommand for Heatmap {
+    type Plugin = NuGraphsPlugin;
+
+    fn name(&self) -> &str {
+        "graph heatmap"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name())
+            .input_output_types(vec![
+                (Type::list(Type::list(Type::Any)), Type::String),
+                (Type::table(), Type::String),
+            ])
+            .named(
+                "x",
+                SyntaxShape::String,
+                "Column of the input table to use for the heatmap columns (default: 'x')",
+                None,
+            )
+            .named(
+                "y",
+                SyntaxShape::String,
+                "Column of the input table to use for the heatmap rows (default: 'y')",
+                None,
+            )
+            .named(
+                "value",
+                SyntaxShape::String,
+                "Column of the input table to use for the cell values (default: 'value')",
+                None,
+            )
+            .named(
+                "gradient",
+                SyntaxShape::Any,
+                "Colors of the cells from low to high, a preset ([viridis*, magma, heat, blues, greys]) or a list of colors (hex strings, color names or {r: int, g: int, b: int})",
+                Some('g'),
+            )
+            .named(
+                "min",
+                SyntaxShape::Any,
+                "Value drawn with the lowest color, smaller values are clamped (default: minimum of the input)",
+                None,
+            )
+            .named(
+                "max",
+                SyntaxShape::Any,
+                "Value drawn with the highest color, bigger values are clamped (default: maximum of the input)",
+                None,
+            )
+            .named(
+                "cell-width",
+                SyntaxShape::Int,
+                "Width of a cell in characters (default: 2)",
+                Some('w'),
+            )
+    }
+
+    fn description(&self) -> &str {
+        "Draw a heatmap of a list of lists or of a table with x, y and value columns"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: nu_protocol::PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::LabeledError> {
+        let columns = HeatmapColumns {
+            x: call.get_flag("x")?,
+            y: call.get_flag("y")?,
+            value: call.get_flag("value")?,
+        };
+        let gradient: Option<GraphGradient> = call.get_flag("gradient")?;
+        let min: Option<GraphValue> = call.get_flag("min")?;
+        let max: Option<GraphValue> = call.get_flag("max")?;
+        let cell_width: Option<Spanned<i64>> = call.get_flag("cell-width")?;
+        let colored = theme(engine)?.colored;
+
+        let cell_width = match cell_width {
+            Some(Spanned { item, .. }) if item > 0 => item as usize,
+            Some(Spanned { span, .. }) => {
+                return Err(nu_protocol::LabeledError::new("Invalid cell width")
+                    .with_label("cell width should be at least 1", span));
+            }
+            None => 2,
+        };
+
+        let HeatmapData {
+            x_labels,
+            y_labels,
+            cells,
+        } = heatmap_from_input(input, &columns, call.head)?;
+
+        let values = cells
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        let unit = unit_of(&values)?;
+        let sorted = sorted_finite(&values);
+        let min = min
+            .map(|min| min.value)
+            .or(sorted.first().copied())
+            .unwrap_or_default();
+        let max = max
+            .map(|max| max.value)
+            .or(sorted.last().copied())
+            .unwrap_or_default();
+
+        let gradient = gradient.unwrap_or_else(|| GraphGradientPreset::default().into());
+        let level = |value: f64| match max > min {
+            true => ((value.clamp(min, max) - min) / (max - min)) as f32,
+            false => 0.0,
+        };
+        let shade = |level: f32, width: usize| match colored {
+            true => paint_cell(gradient.at(level), width),
+            false => SHADES[(level * (SHADES.len() - 1) as f32).round() as usize]
+                .to_string()
+                .repeat(width),
+        };
+
+        let label_width = y_labels
+            .iter()
+            .map(|label| visible_width(label))
+            .max()
+            .unwrap_or_default();
+        let indent = " ".repeat(label_width + 1);
+
+        let mut lines = y_labels
+            .iter()
+            .zip(&cells)
+            .map(|(label, row)| {
+                let row = row
+                    .iter()
+                    .map(|cell| match cell {
+                        Some(GraphValue { value, .. }) if value.is_finite() => {
+                            shade(level(*value), cell_width)
+                        }
+                        _ => " ".repeat(cell_width),
+                    })
+                    .collect::<String>();
+
+                format!("{} {row}", pad_visible(label, label_width))
+            })
+            .collect::<Vec<_>>();
+
+        if !values.is_empty() {
+            lines.push(format!("{indent}{}", x_axis(&x_labels, cell_width)));
+
+            let scale = (0..SCALE_WIDTH)
+                .map(|i| shade(i as f32 / (SCALE_WIDTH - 1) as f32, 1))
+                .collect::<String>();
+            lines.push(String::new());
+            lines.push(format!(
+                "{indent}{} {scale} {}",
+                unit.format(min, max - min),
+                unit.format(max, max - min)
+            ));
+        }
+
+        Ok(nu_protocol::PipelineData::Value(
+            Value::string(lines.join("\n"), call.head),
+            None,
+        ))
+    }
+}
+
+fn paint_cell(color: GraphLineColor, width: usize) -> String {
+    let RGB8 { r, g, b } = color.into();
+
+    Style::new()
+        .on(Color::Rgb(r, g, b))
+        .paint(" ".repeat(width))
+        .to_string()
+}
+
+fn x_axis(labels: &[String], cell_width: usize) -> String {
+    let label_width = labels
+        .iter()
+        .map(|label| visible_width(label) + 1)
+        .max()
+        .unwrap_or_default();
+    let step = label_width.div_ceil(cell_width).max(1);
+
+    labels
+        .iter()
+        .step_by(step)
+        .map(|label| pad_visible(label, step * cell_width))
+        .collect::<String>()
+        .trim_end()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligns_x_labels_by_visible_width() {
+        let colored = Color::Red.paint("ab").to_string();
+        let labels = [colored.clone(), "c".into(), "d".into()];
+        assert_eq!(x_axis(&labels, 2), format!("{colored}  d"));
+
+        let wide = ["日本", "a", "b", "c", "d", "e"].map(String::from);
+        assert_eq!(x_axis(&wide, 1), "日本 e");
+    }
+}
@@ -0,0 +1,155 @@
+use nu_plugin::PluginCommand;
+use nu_protocol::{IntoValue, LabeledError, Signature, Spanned, SyntaxShape, Type};
+
+use crate::{
+    chart::render,
+    input::values_from_input,
+    plugin::NuGraphsPlugin,
+    stats::bins,
+    types::{
+        GraphBin, GraphBinning, GraphConfig, GraphFormat, GraphLineConfig, GraphPoint, GraphType,
+        GraphValue,
+    },
+};
+
+use super::{eval_functions, fit_to_terminal, graph_config_description, theme};
+
+pub struct Histogram;
+
+impl PluginCommand for Histogram {
+    type Plugin = NuGraphsPlugin;
+
+    fn name(&self) -> &str {
+        "graph histogram"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name())
+            .input_output_types(vec![
+                (Type::list(Type::Any), Type::String),
+                (Type::table(), Type::String),
+                (Type::list(Type::Any), Type::table()),
+                (Type::table(), Type::table()),
+            ])
+            .named(
+                "column",
+                SyntaxShape::String,
+                "Column of the input table to bin",
+                Some('c'),
+            )
+            .named("bins", SyntaxShape::Int, "Fixed number of bins", Some('b'))
+            .named(
+                "bin-width",
+                SyntaxShape::Any,
+                "Fixed width of a bin (number, duration or filesize)",
+                Some('w'),
+            )
+            .named(
+                "method",
+                SyntaxShape::String,
+                "Method to compute the number of bins with ([sturges*, freedman-diaconis])",
+                Some('m'),
+            )
+            .switch(
+                "table",
+                "Return the bins as a table of {start, end, count} instead of drawing them",
+                Some('t'),
+            )
+            .optional("graph-config", SyntaxShape::Any, graph_config_description())
+    }
+
+    fn description(&self) -> &str {
+        "Draw a histogram of a list of values"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: nu_protocol::PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::LabeledError> {
+        let column: Option<Spanned<String>> = call.get_flag("column")?;
+        let binning = binning(
+            call.get_flag("bins")?,
+            call.get_flag("bin-width")?,
+            call.get_flag("method")?,
+        )?;
+
+        let values = values_from_input(input, column.as_ref(), call.head)?;
+        let bins = bins(&values, binning)?;
+
+        if call.has_flag("table")? {
+            return Ok(nu_protocol::PipelineData::Value(
+                bins.into_value(call.head),
+                None,
+            ));
+        }
+
+        let mut config: GraphConfig = call.opt(0)?.unwrap_or_default();
+
+        let max_count = bins.iter().map(|bin| bin.count).max().unwrap_or_default();
+        config.y_min.get_or_insert(GraphValue::number(0.0));
+        config
+            .y_max
+            .get_or_insert(GraphValue::number(max_count.max(1) as f64));
+        config.x_label = config.x_label.or(column.map(|column| column.item));
+        config.y_label = config.y_label.or(Some("Count".into()));
+
+        let points = bins
+            .first()
+            .map(|first| GraphPoint {
+                x: first.start,
+                y: GraphValue::number(first.count as f64),
+            })
+            .into_iter()
+            .chain(bins.iter().map(|GraphBin { end, count, .. }| GraphPoint {
+                x: *end,
+                y: GraphValue::number(*count as f64),
+            }))
+            .collect();
+        config.lines.push(GraphLineConfig {
+            ty: Some(GraphType::Bars),
+            points,
+            ..Default::default()
+        });
+        fit_to_terminal(engine, &mut config, GraphFormat::Text)?;
+        eval_functions(engine, &mut config, call.head)?;
+
+        Ok(nu_protocol::PipelineData::Value(
+            render(config, GraphFormat::Text, theme(engine)?, call.head)?,
+            None,
+        ))
+    }
+}
+
+fn binning(
+    count: Option<Spanned<i64>>,
+    width: Option<Spanned<GraphValue>>,
+    method: Option<Spanned<String>>,
+) -> Result<GraphBinning, LabeledError> {
+    match (count, width, method) {
+        (None, None, None) => Ok(GraphBinning::Sturges),
+        (Some(count), None, None) => Ok(GraphBinning::Count(count.item)),
+        (None, Some(width), None) => Ok(GraphBinning::Width(width.item.value)),
+        (None, None, Some(method)) => match method.item.as_str() {
+            "sturges" => Ok(GraphBinning::Sturges),
+            "freedman-diaconis" | "fd" => Ok(GraphBinning::FreedmanDiaconis),
+            _ => Err(LabeledError::new("Unknown binning method")
+                .with_label("expected one of: sturges, freedman-diaconis", method.span)),
+        },
+        (count, width, method) => {
+            let spans = count
+                .map(|count| count.span)
+                .into_iter()
+                .chain(width.map(|width| width.span))
+                .chain(method.map(|method| method.span));
+
+            Err(spans.fold(
+                LabeledError::new("Conflicting binning options")
+                    .with_help("use only one of --bins, --bin-width and --method"),
+                |err, span| err.with_label("conflicts", span),
+            ))
+        }
+    }
+}
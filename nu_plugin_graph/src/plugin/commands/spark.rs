@@ -0,0 +1,110 @@
+use nu_ansi_term::Color;
+use nu_plugin::PluginCommand;
+use nu_protocol::{Signature, Spanned, SyntaxShape, Type, Value};
+use rgb::RGB8;
+
+use crate::{
+    input::values_from_input,
+    plugin::NuGraphsPlugin,
+    stats::sorted_finite,
+    types::{GraphGradient, GraphGradientPreset, GraphValue},
+};
+
+use super::theme;
+
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub struct Spark;
+
+impl PluginCommand for Spark {
+    type Plugin = NuGraphsPlugin;
+
+    fn name(&self) -> &str {
+        "graph spark"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name())
+            .input_output_types(vec![
+                (Type::list(Type::Any), Type::String),
+                (Type::table(), Type::String),
+            ])
+            .named(
+                "column",
+                SyntaxShape::String,
+                "Column of the input table to draw",
+                Some('c'),
+            )
+            .named(
+                "min",
+                SyntaxShape::Any,
+                "Value drawn as the lowest block, smaller values are clamped (default: minimum of the input)",
+                None,
+            )
+            .named(
+                "max",
+                SyntaxShape::Any,
+                "Value drawn as the highest block, bigger values are clamped (default: maximum of the input)",
+                None,
+            )
+            .switch(
+                "color",
+                "Color the blocks by their value, from green (low) to red (high)",
+                None,
+            )
+    }
+
+    fn description(&self) -> &str {
+        "Draw a one-line sparkline of a list of values"
+    }
+
+    fn run(
+        &self,
+        _plugin: &Self::Plugin,
+        engine: &nu_plugin::EngineInterface,
+        call: &nu_plugin::EvaluatedCall,
+        input: nu_protocol::PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::LabeledError> {
+        let column: Option<Spanned<String>> = call.get_flag("column")?;
+        let min: Option<GraphValue> = call.get_flag("min")?;
+        let max: Option<GraphValue> = call.get_flag("max")?;
+        let color = call.has_flag("color")? && theme(engine)?.colored;
+
+        let values = values_from_input(input, column.as_ref(), call.head)?;
+        let sorted = sorted_finite(&values);
+
+        let min = min
+            .map(|min| min.value)
+            .or(sorted.first().copied())
+            .unwrap_or_default();
+        let max = max
+            .map(|max| max.value)
+            .or(sorted.last().copied())
+            .unwrap_or_default();
+
+        let gradient = GraphGradient::from(GraphGradientPreset::Heat);
+        let spark = values
+            .iter()
+            .map(|GraphValue { value, .. }| {
+                let level = match max > min && value.is_finite() {
+                    true => ((value.clamp(min, max) - min) / (max - min)) as f32,
+                    false => 0.0,
+                };
+                let block = BLOCKS[(level * (BLOCKS.len() - 1) as f32).round() as usize];
+
+                match color {
+                    true => {
+                        let RGB8 { r, g, b } = gradient.at(level).into();
+                        Color::Rgb(r, g, b).paint(block.to_string()).to_string()
+                    }
+                    false => block.to_string(),
+                }
+            })
+            .collect::<String>();
+
+        Ok(nu_protocol::PipelineData::Value(
+            Value::string(spark, call.head),
+            None,
+        ))
+    }
+}
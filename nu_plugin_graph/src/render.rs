@@ -0,0 +1,10 @@
+mod layout;
+mod raster;
+mod svg;
+mod terminal;
+mod text;
+
+pub use raster::render_png;
+pub use svg::render_svg;
+pub use terminal::{render_kitty, render_sixel};
+pub use text::render_text;
@@ -0,0 +1,179 @@
+use crate::{chart::Plot, types::GraphLegendPosition};
+
+pub struct Metrics {
+    pub scale: f32,
+    pub font_size: f32,
+    pub char_width: f32,
+    pub line_height: f32,
+    pub swatch: f32,
+}
+
+pub struct Frame {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+    pub x_range: (f32, f32),
+    pub y_range: (f32, f32),
+}
+
+impl Frame {
+    pub fn x(&self, x: f32) -> f32 {
+        self.left + fraction(x, self.x_range) * self.width
+    }
+
+    pub fn y(&self, y: f32) -> f32 {
+        self.top + self.height - fraction(y, self.y_range) * self.height
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.top + self.height
+    }
+
+    pub fn right(&self) -> f32 {
+        self.left + self.width
+    }
+}
+
+pub enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+pub struct Layout {
+    pub frame: Frame,
+    pub legend: (f32, f32),
+    pub legend_size: (f32, f32),
+    pub y2_label: f32,
+    pub size: (f32, f32),
+}
+
+impl Layout {
+    pub fn new(plot: &Plot, metrics: &Metrics) -> Self {
+        let legend_size = legend_size(plot, metrics);
+        let header = match plot.no_header {
+            true => 0.0,
+            false => metrics.line_height,
+        };
+        let y_figures_width = y_figures(plot)
+            .iter()
+            .map(|(_, label)| label.chars().count() as f32 * metrics.char_width)
+            .fold(0.0, f32::max);
+
+        let y2_labels = y2_figures(plot);
+        let y2_width = match y2_labels.is_empty() {
+            true => 0.0,
+            false => {
+                y2_labels
+                    .iter()
+                    .map(|(_, label)| label.chars().count() as f32 * metrics.char_width)
+                    .fold(0.0, f32::max)
+                    + 14.0
+                    + header
+            }
+        };
+
+        let frame = Frame {
+            left: 10.0 + header + y_figures_width + 8.0,
+            top: 10.0 + metrics.font_size / 2.0,
+            width: plot.width as f32 * metrics.scale,
+            height: plot.height as f32 * metrics.scale,
+            x_range: plot.x_range,
+            y_range: plot.y_range,
+        };
+
+        let y2_label = frame.right() + y2_width - header + metrics.font_size;
+
+        let below_frame = frame.bottom() + metrics.line_height * 1.5 + header;
+        let legend = match plot.legend.position {
+            GraphLegendPosition::Bottom => (frame.left, below_frame),
+            GraphLegendPosition::Right => (frame.right() + y2_width + 20.0, frame.top),
+        };
+        let size = match plot.legend.position {
+            GraphLegendPosition::Bottom => (
+                f32::max(frame.right() + y2_width, legend.0 + legend_size.0) + 10.0,
+                legend.1 + legend_size.1 + 10.0,
+            ),
+            GraphLegendPosition::Right => (
+                legend.0 + legend_size.0 + 10.0,
+                f32::max(below_frame, legend.1 + legend_size.1) + 10.0,
+            ),
+        };
+
+        Self {
+            frame,
+            legend,
+            legend_size,
+            y2_label,
+            size,
+        }
+    }
+}
+
+pub fn y_figures(plot: &Plot) -> Vec<(f32, String)> {
+    let (y_min, y_max) = plot.y_range;
+    let ys = match plot.y_ticks.is_empty() {
+        true => vec![y_max, y_min],
+        false => plot.y_ticks.clone(),
+    };
+
+    ys.into_iter().map(|y| (y, plot.y_axis.format(y))).collect()
+}
+
+pub fn y2_figures(plot: &Plot) -> Vec<(f32, String)> {
+    let Some(y2) = &plot.y2 else {
+        return vec![];
+    };
+
+    let (y_min, y_max) = y2.range;
+    let ys = match y2.ticks.is_empty() {
+        true => vec![y_max, y_min],
+        false => y2.ticks.clone(),
+    };
+
+    ys.into_iter()
+        .map(|y| (y2.to_primary(y, plot.y_range), y2.axis.format(y)))
+        .collect()
+}
+
+pub fn x_figures(plot: &Plot) -> Vec<(f32, String, Anchor)> {
+    let (x_min, x_max) = plot.x_range;
+    let xs = match plot.x_ticks.is_empty() {
+        true => vec![(x_min, Anchor::Start), (x_max, Anchor::End)],
+        false => plot.x_ticks.iter().map(|x| (*x, Anchor::Middle)).collect(),
+    };
+
+    xs.into_iter()
+        .map(|(x, anchor)| (x, plot.x_axis.format(x), anchor))
+        .collect()
+}
+
+fn legend_size(plot: &Plot, metrics: &Metrics) -> (f32, f32) {
+    let legend = &plot.legend;
+    if legend.entries.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let title = match legend.no_header {
+        true => 0,
+        false => 1,
+    };
+    let label_width = legend
+        .entries
+        .iter()
+        .map(|entry| entry.label.chars().count() as f32 * metrics.char_width + metrics.swatch + 6.0)
+        .fold(title as f32 * 6.0 * metrics.char_width, f32::max);
+
+    (
+        label_width + 16.0,
+        (legend.entries.len() + title) as f32 * metrics.line_height + 8.0,
+    )
+}
+
+fn fraction(value: f32, (min, max): (f32, f32)) -> f32 {
+    match max - min {
+        range if range.is_finite() && range != 0.0 => (value - min) / range,
+        _ => 0.5,
+    }
+}
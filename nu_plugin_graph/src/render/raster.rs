@@ -0,0 +1,619 @@
+use std::convert::Infallible;
+
+use embedded_graphics::{
+    Pixel,
+    draw_target::DrawTargetExt,
+    mono_font::{MonoFont, MonoTextStyle, iso_8859_1::FONT_7X13},
+    pixelcolor::Rgb888,
+    prelude::*,
+    primitives::{Circle, Line, Polyline, PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+use nu_protocol::LabeledError;
+use rgb::RGB8;
+
+use super::layout::{Anchor, Frame, Layout, Metrics, x_figures, y_figures, y2_figures};
+use crate::{
+    chart::{Plot, PlotAnnotation, PlotAnnotationKind, PlotLine},
+    legend::LegendEntry,
+    types::{GraphAxisStyle, GraphLineColor, GraphType},
+};
+
+const FONT: MonoFont = FONT_7X13;
+const METRICS: Metrics = Metrics {
+    scale: 6.0,
+    font_size: 13.0,
+    char_width: 7.0,
+    line_height: 18.0,
+    swatch: 12.0,
+};
+const BACKGROUND: Rgb888 = Rgb888::new(0xff, 0xff, 0xff);
+const FOREGROUND: Rgb888 = Rgb888::new(0x20, 0x20, 0x20);
+
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, color: Rgb888) -> Self {
+        Self {
+            width,
+            height,
+            data: [color.r(), color.g(), color.b()].repeat((width * height) as usize),
+        }
+    }
+
+    fn index(&self, Point { x, y }: Point) -> Option<usize> {
+        match (0..self.width as i32).contains(&x) && (0..self.height as i32).contains(&y) {
+            true => Some((y as usize * self.width as usize + x as usize) * 3),
+            false => None,
+        }
+    }
+
+    fn get(&self, point: Point) -> Option<Rgb888> {
+        self.index(point)
+            .map(|i| Rgb888::new(self.data[i], self.data[i + 1], self.data[i + 2]))
+    }
+
+    fn set(&mut self, point: Point, color: Rgb888) {
+        if let Some(i) = self.index(point) {
+            self.data[i..i + 3].copy_from_slice(&[color.r(), color.g(), color.b()]);
+        }
+    }
+
+    fn blend_rect(&mut self, rect: &Rectangle, color: Rgb888, alpha: f32) {
+        for point in rect.points() {
+            if let Some(under) = self.get(point) {
+                self.set(point, mix(under, color, alpha));
+            }
+        }
+    }
+}
+
+impl OriginDimensions for Canvas {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+impl DrawTarget for Canvas {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            self.set(point, color);
+        }
+
+        Ok(())
+    }
+}
+
+pub fn render_png(plot: &Plot) -> Result<Vec<u8>, LabeledError> {
+    let canvas = rasterize(plot);
+    let mut bytes = vec![];
+
+    let mut encoder = png::Encoder::new(&mut bytes, canvas.width, canvas.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| {
+            writer.write_image_data(&canvas.data)?;
+            writer.finish()
+        })
+        .map_err(|err| LabeledError::new("Failed to encode PNG").with_help(err.to_string()))?;
+
+    Ok(bytes)
+}
+
+pub fn rasterize(plot: &Plot) -> Canvas {
+    let Layout {
+        frame,
+        legend: legend_position,
+        legend_size,
+        y2_label,
+        size: (width, height),
+    } = Layout::new(plot, &METRICS);
+
+    let mut canvas = Canvas::new(width.ceil() as u32, height.ceil() as u32, BACKGROUND);
+    let area = Rectangle::with_corners(
+        point((frame.left, frame.top)),
+        point((frame.right(), frame.bottom())),
+    );
+
+    if !plot.no_borders {
+        let corners = [
+            (frame.left, frame.top),
+            (frame.right(), frame.top),
+            (frame.right(), frame.bottom()),
+            (frame.left, frame.bottom()),
+        ];
+        for i in 0..corners.len() {
+            styled_line(
+                &mut canvas,
+                GraphAxisStyle::Dotted,
+                mix(BACKGROUND, FOREGROUND, 0.5),
+                corners[i],
+                corners[(i + 1) % corners.len()],
+            );
+        }
+    }
+
+    if !plot.no_axis {
+        let axis_color = mix(BACKGROUND, FOREGROUND, 0.7);
+
+        if let Some(y) = plot.x_axis_position() {
+            styled_line(
+                &mut canvas,
+                plot.x_style,
+                axis_color,
+                (frame.left, frame.y(y)),
+                (frame.right(), frame.y(y)),
+            );
+        }
+
+        if let Some(x) = plot.y_axis_position() {
+            styled_line(
+                &mut canvas,
+                plot.y_style,
+                axis_color,
+                (frame.x(x), frame.top),
+                (frame.x(x), frame.bottom()),
+            );
+        }
+
+        if let Some(y2) = &plot.y2 {
+            styled_line(
+                &mut canvas,
+                y2.style,
+                axis_color,
+                (frame.right(), frame.top),
+                (frame.right(), frame.bottom()),
+            );
+        }
+    }
+
+    if !plot.no_figures {
+        for line in &plot.lines {
+            series(&mut canvas, &frame, &area, line);
+        }
+        for annotation in &plot.annotations {
+            annotation_shape(&mut canvas, &frame, &area, annotation);
+        }
+        for annotation in &plot.annotations {
+            annotation_label(&mut canvas, &frame, annotation);
+        }
+    }
+
+    figures(&mut canvas, plot, &frame);
+
+    if !plot.no_header {
+        text(
+            &mut canvas,
+            &plot.x_label,
+            (
+                frame.left + frame.width / 2.0,
+                frame.bottom() + METRICS.line_height * 2.0,
+            ),
+            Alignment::Center,
+        );
+        vertical_text(
+            &mut canvas,
+            &plot.y_label,
+            (10.0 + METRICS.font_size, frame.top + frame.height / 2.0),
+        );
+
+        if let Some(y2) = &plot.y2 {
+            vertical_text(
+                &mut canvas,
+                &y2.label,
+                (y2_label, frame.top + frame.height / 2.0),
+            );
+        }
+    }
+
+    legend(&mut canvas, plot, legend_position, legend_size);
+
+    canvas
+}
+
+fn series(canvas: &mut Canvas, frame: &Frame, area: &Rectangle, line: &PlotLine) {
+    let color = line_color(line.color);
+    let coords = line
+        .points
+        .iter()
+        .map(|(x, y)| point((frame.x(*x), frame.y(*y))))
+        .collect::<Vec<_>>();
+
+    match line.ty {
+        GraphType::Points => {
+            for coord in coords {
+                let _ = Circle::with_center(coord, 5)
+                    .into_styled(PrimitiveStyle::with_fill(color))
+                    .draw(&mut canvas.clipped(area));
+            }
+        }
+        GraphType::Lines => {
+            if line.base.is_some() {
+                fill(canvas, frame, area, line, color);
+            }
+            let _ = Polyline::new(&coords)
+                .into_styled(PrimitiveStyle::with_stroke(color, 2))
+                .draw(&mut canvas.clipped(area));
+        }
+        GraphType::Steps => {
+            let steps = coords
+                .iter()
+                .enumerate()
+                .flat_map(|(i, coord)| match i {
+                    0 => vec![*coord],
+                    _ => vec![Point::new(coords[i - 1].x, coord.y), *coord],
+                })
+                .collect::<Vec<_>>();
+            let _ = Polyline::new(&steps)
+                .into_styled(PrimitiveStyle::with_stroke(color, 2))
+                .draw(&mut canvas.clipped(area));
+        }
+        GraphType::Bars => {
+            let bottoms = match &line.base {
+                Some(base) => base.iter().map(|y| frame.y(*y).round() as i32).collect(),
+                None => vec![frame.bottom().round() as i32; coords.len()],
+            };
+            for (pair, bottom) in coords.windows(2).zip(&bottoms[1..]) {
+                let bar = Rectangle::with_corners(
+                    Point::new(pair[0].x, *bottom),
+                    Point::new(pair[1].x, pair[1].y),
+                );
+                canvas.blend_rect(&bar.intersection(area), color, 0.3);
+                let _ = bar
+                    .into_styled(PrimitiveStyle::with_stroke(color, 1))
+                    .draw(&mut canvas.clipped(area));
+            }
+        }
+    }
+}
+
+fn fill(canvas: &mut Canvas, frame: &Frame, area: &Rectangle, line: &PlotLine, color: Rgb888) {
+    let (x_min, x_max) = frame.x_range;
+    for column in frame.left.round() as i32..=frame.right().round() as i32 {
+        let x = x_min + (column as f32 - frame.left) / frame.width * (x_max - x_min);
+        let Some((from, to)) = line.fill_at(x) else {
+            continue;
+        };
+
+        let strip = Rectangle::with_corners(
+            Point::new(column, frame.y(from).round() as i32),
+            Point::new(column, frame.y(to).round() as i32),
+        );
+        canvas.blend_rect(&strip.intersection(area), color, 0.3);
+    }
+}
+
+fn annotation_shape(
+    canvas: &mut Canvas,
+    frame: &Frame,
+    area: &Rectangle,
+    PlotAnnotation {
+        kind, color, style, ..
+    }: &PlotAnnotation,
+) {
+    let color = line_color(*color);
+
+    match *kind {
+        PlotAnnotationKind::HLine(y) if (frame.top..=frame.bottom()).contains(&frame.y(y)) => {
+            styled_line(
+                canvas,
+                *style,
+                color,
+                (frame.left, frame.y(y)),
+                (frame.right(), frame.y(y)),
+            );
+        }
+        PlotAnnotationKind::VLine(x) if (frame.left..=frame.right()).contains(&frame.x(x)) => {
+            styled_line(
+                canvas,
+                *style,
+                color,
+                (frame.x(x), frame.top),
+                (frame.x(x), frame.bottom()),
+            );
+        }
+        PlotAnnotationKind::Band(from, to) => {
+            let band = Rectangle::with_corners(
+                point((frame.left, frame.y(from.max(to)))),
+                point((frame.right(), frame.y(from.min(to)))),
+            );
+            canvas.blend_rect(&band.intersection(area), color, 0.15);
+        }
+        PlotAnnotationKind::Text(x, y) => {
+            let _ = Circle::with_center(point((frame.x(x), frame.y(y))), 7)
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(&mut canvas.clipped(area));
+        }
+        _ => {}
+    }
+}
+
+fn annotation_label(
+    canvas: &mut Canvas,
+    frame: &Frame,
+    PlotAnnotation { kind, label, .. }: &PlotAnnotation,
+) {
+    let Some(label) = label else {
+        return;
+    };
+
+    let half = METRICS.line_height / 2.0;
+    let (x, y, alignment) = match *kind {
+        PlotAnnotationKind::HLine(y) => (frame.right() - 4.0, frame.y(y) - half, Alignment::Right),
+        PlotAnnotationKind::VLine(x) => (frame.x(x) + 4.0, frame.top + half, Alignment::Left),
+        PlotAnnotationKind::Band(from, to) => (
+            frame.left + 4.0,
+            frame.y(from.max(to)) + half,
+            Alignment::Left,
+        ),
+        PlotAnnotationKind::Text(x, y) => (frame.x(x) + 6.0, frame.y(y) - half, Alignment::Left),
+    };
+    if !(frame.left..=frame.right()).contains(&x) || !(frame.top..=frame.bottom()).contains(&y) {
+        return;
+    }
+
+    text(canvas, label, (x, y), alignment);
+}
+
+fn figures(canvas: &mut Canvas, plot: &Plot, frame: &Frame) {
+    for (y, label) in y_figures(plot) {
+        text(
+            canvas,
+            &label,
+            (frame.left - 6.0, frame.y(y)),
+            Alignment::Right,
+        );
+    }
+
+    for (y, label) in y2_figures(plot) {
+        text(
+            canvas,
+            &label,
+            (frame.right() + 6.0, frame.y(y)),
+            Alignment::Left,
+        );
+    }
+
+    for (x, label, anchor) in x_figures(plot) {
+        let alignment = match anchor {
+            Anchor::Start => Alignment::Left,
+            Anchor::Middle => Alignment::Center,
+            Anchor::End => Alignment::Right,
+        };
+        text(
+            canvas,
+            &label,
+            (frame.x(x), frame.bottom() + METRICS.line_height),
+            alignment,
+        );
+    }
+}
+
+fn legend(canvas: &mut Canvas, plot: &Plot, (x, y): (f32, f32), (width, height): (f32, f32)) {
+    let legend = &plot.legend;
+    if legend.entries.is_empty() {
+        return;
+    }
+
+    if !legend.no_borders {
+        let _ = Rectangle::with_corners(point((x, y)), point((x + width, y + height)))
+            .into_styled(PrimitiveStyle::with_stroke(
+                mix(BACKGROUND, FOREGROUND, 0.5),
+                1,
+            ))
+            .draw(canvas);
+    }
+
+    let mut row = y + METRICS.line_height / 2.0 + 4.0;
+    if !legend.no_header {
+        text(canvas, "Legend", (x + 8.0, row), Alignment::Left);
+        row += METRICS.line_height;
+    }
+
+    for LegendEntry { color, label } in &legend.entries {
+        let _ = Rectangle::new(
+            point((x + 8.0, row - METRICS.swatch / 2.0)),
+            Size::new(METRICS.swatch as u32, METRICS.swatch as u32),
+        )
+        .into_styled(PrimitiveStyle::with_fill(line_color(*color)))
+        .draw(canvas);
+        text(
+            canvas,
+            label,
+            (x + 8.0 + METRICS.swatch + 6.0, row),
+            Alignment::Left,
+        );
+        row += METRICS.line_height;
+    }
+}
+
+fn styled_line(
+    canvas: &mut Canvas,
+    style: GraphAxisStyle,
+    color: Rgb888,
+    from: (f32, f32),
+    to: (f32, f32),
+) {
+    let line = |from, to| {
+        Line::new(point(from), point(to)).into_styled(PrimitiveStyle::with_stroke(color, 1))
+    };
+    let (on, off) = match style {
+        GraphAxisStyle::None => return,
+        GraphAxisStyle::Solid => {
+            let _ = line(from, to).draw(canvas);
+            return;
+        }
+        GraphAxisStyle::Dotted => (1.0, 4.0),
+        GraphAxisStyle::Dashed => (6.0, 4.0),
+    };
+
+    let length = f32::hypot(to.0 - from.0, to.1 - from.1);
+    let at = |distance: f32| {
+        let t = (distance / length).min(1.0);
+        (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+    };
+
+    let mut distance = 0.0;
+    while distance <= length {
+        let _ = line(at(distance), at(distance + on - 1.0)).draw(canvas);
+        distance += on + off;
+    }
+}
+
+fn text(canvas: &mut Canvas, text: &str, position: (f32, f32), alignment: Alignment) {
+    let style = TextStyleBuilder::new()
+        .alignment(alignment)
+        .baseline(Baseline::Middle)
+        .build();
+
+    let _ = Text::with_text_style(
+        text,
+        point(position),
+        MonoTextStyle::new(&FONT, FOREGROUND),
+        style,
+    )
+    .draw(canvas);
+}
+
+fn vertical_text(canvas: &mut Canvas, content: &str, (x, y): (f32, f32)) {
+    let width = content.chars().count() as u32 * FONT.character_size.width;
+    let height = FONT.character_size.height;
+
+    let mut label = Canvas::new(width, height, BACKGROUND);
+    text(
+        &mut label,
+        content,
+        (0.0, height as f32 / 2.0),
+        Alignment::Left,
+    );
+
+    let origin = point((x - height as f32 / 2.0, y + width as f32 / 2.0));
+    for point in Rectangle::new(Point::zero(), label.size()).points() {
+        if let Some(color) = label.get(point).filter(|color| *color != BACKGROUND) {
+            canvas.set(origin + Point::new(point.y, -point.x), color);
+        }
+    }
+}
+
+fn line_color(color: Option<GraphLineColor>) -> Rgb888 {
+    match color {
+        Some(color) => {
+            let RGB8 { r, g, b } = color.into();
+            Rgb888::new(r, g, b)
+        }
+        None => FOREGROUND,
+    }
+}
+
+fn mix(from: Rgb888, to: Rgb888, alpha: f32) -> Rgb888 {
+    let channel =
+        |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * alpha).round() as u8;
+
+    Rgb888::new(
+        channel(from.r(), to.r()),
+        channel(from.g(), to.g()),
+        channel(from.b(), to.b()),
+    )
+}
+
+fn point((x, y): (f32, f32)) -> Point {
+    Point::new(x.round() as i32, y.round() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{
+        theme::Theme,
+        types::{GraphConfig, GraphLineConfig, GraphPoint, GraphSize, GraphValue},
+    };
+
+    use super::*;
+
+    const ORANGE: Rgb888 = Rgb888::new(255, 136, 0);
+
+    fn plot(no_figures: bool) -> Plot {
+        let config = GraphConfig {
+            ty: Some(GraphType::Lines),
+            width: Some(GraphSize::Dots(40)),
+            height: Some(GraphSize::Dots(20)),
+            no_figures: Some(no_figures),
+            lines: vec![GraphLineConfig {
+                label: Some("load".into()),
+                color: Some(GraphLineColor::new(255, 136, 0)),
+                points: (0..=10)
+                    .map(|x| GraphPoint {
+                        x: GraphValue::number(x as f64),
+                        y: GraphValue::number(x as f64),
+                    })
+                    .collect(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        Plot::new(config, Theme::default()).unwrap()
+    }
+
+    fn pixels(canvas: &Canvas) -> impl Iterator<Item = Rgb888> {
+        canvas
+            .data
+            .chunks(3)
+            .map(|pixel| Rgb888::new(pixel[0], pixel[1], pixel[2]))
+    }
+
+    #[test]
+    fn encodes_the_canvas_as_an_rgb_png() {
+        let plot = plot(false);
+        let canvas = rasterize(&plot);
+        let bytes = render_png(&plot).unwrap();
+        assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        let mut reader = png::Decoder::new(Cursor::new(bytes)).read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut data).unwrap();
+        assert_eq!((info.width, info.height), (canvas.width, canvas.height));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(data, canvas.data);
+    }
+
+    #[test]
+    fn draws_the_line_from_corner_to_corner() {
+        let plot = plot(false);
+        let canvas = rasterize(&plot);
+        let Layout { frame, .. } = Layout::new(&plot, &METRICS);
+
+        assert_eq!(pixels(&canvas).next(), Some(BACKGROUND));
+        for (x, y) in [(0.0, 0.0), (5.0, 5.0), (10.0, 10.0)] {
+            let center = point((frame.x(x), frame.y(y)));
+            let near = (-1..=1)
+                .flat_map(|dx| (-1..=1).map(move |dy| center + Point::new(dx, dy)))
+                .filter_map(|point| canvas.get(point))
+                .collect::<Vec<_>>();
+            assert!(near.contains(&ORANGE), "no line near ({x}, {y})");
+        }
+        assert_eq!(
+            canvas.get(point((frame.x(8.0), frame.y(2.0)))),
+            Some(BACKGROUND)
+        );
+
+        // only the legend swatch is left without figures
+        let orange = |canvas: &Canvas| pixels(canvas).filter(|pixel| *pixel == ORANGE).count();
+        let swatch = (METRICS.swatch * METRICS.swatch) as usize;
+        assert_eq!(orange(&rasterize(&self::plot(true))), swatch);
+        assert!(orange(&canvas) > swatch + frame.width as usize);
+    }
+}
@@ -0,0 +1,517 @@
+use std::fmt::Write;
+
+use rgb::RGB8;
+
+use super::layout::{Anchor, Frame, Layout, Metrics, x_figures, y_figures, y2_figures};
+use crate::{
+    chart::{Plot, PlotAnnotation, PlotAnnotationKind, PlotLine},
+    legend::LegendEntry,
+    types::{GraphAxisStyle, GraphLineColor, GraphType},
+};
+
+const FONT_SIZE: f32 = 12.0;
+const METRICS: Metrics = Metrics {
+    scale: 6.0,
+    font_size: FONT_SIZE,
+    char_width: FONT_SIZE * 0.6,
+    line_height: FONT_SIZE * 1.5,
+    swatch: 12.0,
+};
+
+pub fn render_svg(plot: &Plot) -> String {
+    let Layout {
+        frame,
+        legend: legend_position,
+        legend_size,
+        y2_label,
+        size: (total_width, total_height),
+    } = Layout::new(plot, &METRICS);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{total_width:.0}" height="{total_height:.0}" viewBox="0 0 {total_width:.0} {total_height:.0}" font-family="monospace" font-size="{FONT_SIZE}">"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"<defs><clipPath id="plot-area"><rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}"/></clipPath></defs>"#,
+        frame.left, frame.top, frame.width, frame.height
+    );
+
+    if !plot.no_borders {
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="none" stroke="currentColor" stroke-opacity="0.5"{}/>"#,
+            frame.left,
+            frame.top,
+            frame.width,
+            frame.height,
+            dash(GraphAxisStyle::Dotted)
+        );
+    }
+
+    if !plot.no_axis {
+        if let Some(y) = plot.x_axis_position() {
+            axis_line(
+                &mut svg,
+                plot.x_style,
+                (frame.left, frame.y(y)),
+                (frame.right(), frame.y(y)),
+            );
+        }
+
+        if let Some(x) = plot.y_axis_position() {
+            axis_line(
+                &mut svg,
+                plot.y_style,
+                (frame.x(x), frame.top),
+                (frame.x(x), frame.bottom()),
+            );
+        }
+
+        if let Some(y2) = &plot.y2 {
+            axis_line(
+                &mut svg,
+                y2.style,
+                (frame.right(), frame.top),
+                (frame.right(), frame.bottom()),
+            );
+        }
+    }
+
+    if !plot.no_figures {
+        let _ = writeln!(svg, r#"<g clip-path="url(#plot-area)">"#);
+        for line in &plot.lines {
+            series(&mut svg, &frame, line);
+        }
+        for annotation in &plot.annotations {
+            annotation_shape(&mut svg, &frame, annotation);
+        }
+        let _ = writeln!(svg, "</g>");
+
+        for annotation in &plot.annotations {
+            annotation_label(&mut svg, &frame, annotation);
+        }
+    }
+
+    figures(&mut svg, plot, &frame);
+
+    if !plot.no_header {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+            frame.left + frame.width / 2.0,
+            frame.bottom() + METRICS.line_height * 2.0,
+            escape(&plot.x_label)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{0:.1}" y="{1:.1}" text-anchor="middle" transform="rotate(-90 {0:.1} {1:.1})">{2}</text>"#,
+            10.0 + FONT_SIZE,
+            frame.top + frame.height / 2.0,
+            escape(&plot.y_label)
+        );
+
+        if let Some(y2) = &plot.y2 {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{0:.1}" y="{1:.1}" text-anchor="middle" transform="rotate(-90 {0:.1} {1:.1})">{2}</text>"#,
+                y2_label,
+                frame.top + frame.height / 2.0,
+                escape(&y2.label)
+            );
+        }
+    }
+
+    legend(&mut svg, plot, legend_position, legend_size);
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn series(
+    svg: &mut String,
+    frame: &Frame,
+    PlotLine {
+        ty,
+        color,
+        points,
+        base,
+    }: &PlotLine,
+) {
+    let color = color_str(*color);
+    let coords = points
+        .iter()
+        .map(|(x, y)| (frame.x(*x), frame.y(*y)))
+        .collect::<Vec<_>>();
+    let bottoms = match base {
+        Some(base) => base.iter().map(|y| frame.y(*y)).collect(),
+        None => vec![frame.bottom(); coords.len()],
+    };
+
+    if let (GraphType::Lines, Some(_)) = (ty, base) {
+        let area = coords
+            .iter()
+            .copied()
+            .chain(
+                coords
+                    .iter()
+                    .zip(&bottoms)
+                    .rev()
+                    .map(|((x, _), y)| (*x, *y)),
+            )
+            .map(|(x, y)| format!("{x:.1},{y:.1}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            svg,
+            r#"<polygon points="{area}" fill="{color}" fill-opacity="0.3"/>"#
+        );
+    }
+
+    match ty {
+        GraphType::Points => {
+            for (x, y) in coords {
+                let _ = writeln!(
+                    svg,
+                    r#"<circle cx="{x:.1}" cy="{y:.1}" r="2" fill="{color}"/>"#
+                );
+            }
+        }
+        GraphType::Lines => {
+            let points = coords
+                .iter()
+                .map(|(x, y)| format!("{x:.1},{y:.1}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = writeln!(
+                svg,
+                r#"<polyline points="{points}" fill="none" stroke="{color}" stroke-width="1.5"/>"#
+            );
+        }
+        GraphType::Steps => {
+            let Some((x, y)) = coords.first() else {
+                return;
+            };
+            let path = coords
+                .iter()
+                .skip(1)
+                .fold(format!("M{x:.1} {y:.1}"), |path, (x, y)| {
+                    format!("{path} V{y:.1} H{x:.1}")
+                });
+            let _ = writeln!(
+                svg,
+                r#"<path d="{path}" fill="none" stroke="{color}" stroke-width="1.5"/>"#
+            );
+        }
+        GraphType::Bars => {
+            for (pair, bottom) in coords.windows(2).zip(&bottoms[1..]) {
+                let ((x1, _), (x2, y2)) = (pair[0], pair[1]);
+                let _ = writeln!(
+                    svg,
+                    r#"<path d="M{x1:.1} {bottom:.1} V{y2:.1} H{x2:.1} V{bottom:.1}" fill="{color}" fill-opacity="0.3" stroke="{color}"/>"#
+                );
+            }
+        }
+    }
+}
+
+fn annotation_shape(
+    svg: &mut String,
+    frame: &Frame,
+    PlotAnnotation {
+        kind, color, style, ..
+    }: &PlotAnnotation,
+) {
+    let color = color_str(*color);
+
+    match *kind {
+        PlotAnnotationKind::HLine(y) if !matches!(style, GraphAxisStyle::None) => {
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{:.1}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="{color}"{}/>"#,
+                frame.left,
+                frame.right(),
+                dash(*style),
+                y = frame.y(y)
+            );
+        }
+        PlotAnnotationKind::VLine(x) if !matches!(style, GraphAxisStyle::None) => {
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{x:.1}" y1="{:.1}" x2="{x:.1}" y2="{:.1}" stroke="{color}"{}/>"#,
+                frame.top,
+                frame.bottom(),
+                dash(*style),
+                x = frame.x(x)
+            );
+        }
+        PlotAnnotationKind::Band(from, to) => {
+            let (top, bottom) = (frame.y(from.max(to)), frame.y(from.min(to)));
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{:.1}" y="{top:.1}" width="{:.1}" height="{:.1}" fill="{color}" fill-opacity="0.15"/>"#,
+                frame.left,
+                frame.width,
+                bottom - top
+            );
+        }
+        PlotAnnotationKind::Text(x, y) => {
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="{color}"/>"#,
+                frame.x(x),
+                frame.y(y)
+            );
+        }
+        _ => {}
+    }
+}
+
+fn annotation_label(
+    svg: &mut String,
+    frame: &Frame,
+    PlotAnnotation {
+        kind, label, color, ..
+    }: &PlotAnnotation,
+) {
+    let Some(label) = label else {
+        return;
+    };
+
+    let (x, y, anchor) = match *kind {
+        PlotAnnotationKind::HLine(y) => (frame.right() - 4.0, frame.y(y) - 4.0, "end"),
+        PlotAnnotationKind::VLine(x) => (frame.x(x) + 4.0, frame.top + FONT_SIZE, "start"),
+        PlotAnnotationKind::Band(from, to) => {
+            (frame.left + 4.0, frame.y(from.max(to)) + FONT_SIZE, "start")
+        }
+        PlotAnnotationKind::Text(x, y) => (frame.x(x) + 6.0, frame.y(y) - 4.0, "start"),
+    };
+    if !(frame.left..=frame.right()).contains(&x) || !(frame.top..=frame.bottom()).contains(&y) {
+        return;
+    }
+
+    let _ = writeln!(
+        svg,
+        r#"<text x="{x:.1}" y="{y:.1}" text-anchor="{anchor}" fill="{}">{}</text>"#,
+        color_str(*color),
+        escape(label)
+    );
+}
+
+fn figures(svg: &mut String, plot: &Plot, frame: &Frame) {
+    for (y, text) in y_figures(plot) {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end" dominant-baseline="middle">{}</text>"#,
+            frame.left - 6.0,
+            frame.y(y),
+            escape(&text)
+        );
+    }
+
+    for (y, text) in y2_figures(plot) {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="start" dominant-baseline="middle">{}</text>"#,
+            frame.right() + 6.0,
+            frame.y(y),
+            escape(&text)
+        );
+    }
+
+    for (x, text, anchor) in x_figures(plot) {
+        let anchor = match anchor {
+            Anchor::Start => "start",
+            Anchor::Middle => "middle",
+            Anchor::End => "end",
+        };
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="{anchor}">{}</text>"#,
+            frame.x(x),
+            frame.bottom() + METRICS.line_height,
+            escape(&text)
+        );
+    }
+}
+
+fn legend(svg: &mut String, plot: &Plot, (x, y): (f32, f32), (width, height): (f32, f32)) {
+    let legend = &plot.legend;
+    if legend.entries.is_empty() {
+        return;
+    }
+
+    if !legend.no_borders {
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{x:.1}" y="{y:.1}" width="{width:.1}" height="{height:.1}" fill="none" stroke="currentColor" stroke-opacity="0.5"/>"#
+        );
+    }
+
+    let mut row = y + METRICS.line_height / 2.0 + 4.0;
+    if !legend.no_header {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{row:.1}" dominant-baseline="middle">Legend</text>"#,
+            x + 8.0
+        );
+        row += METRICS.line_height;
+    }
+
+    for LegendEntry { color, label } in &legend.entries {
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{swatch}" height="{swatch}" fill="{}"/>"#,
+            x + 8.0,
+            row - METRICS.swatch / 2.0,
+            color_str(*color),
+            swatch = METRICS.swatch,
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{row:.1}" dominant-baseline="middle">{}</text>"#,
+            x + 8.0 + METRICS.swatch + 6.0,
+            escape(label)
+        );
+        row += METRICS.line_height;
+    }
+}
+
+fn axis_line(svg: &mut String, style: GraphAxisStyle, (x1, y1): (f32, f32), (x2, y2): (f32, f32)) {
+    if let GraphAxisStyle::None = style {
+        return;
+    }
+
+    let _ = writeln!(
+        svg,
+        r#"<line x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" stroke="currentColor" stroke-opacity="0.7"{}/>"#,
+        dash(style)
+    );
+}
+
+fn dash(style: GraphAxisStyle) -> &'static str {
+    match style {
+        GraphAxisStyle::None | GraphAxisStyle::Solid => "",
+        GraphAxisStyle::Dotted => r#" stroke-dasharray="1,4""#,
+        GraphAxisStyle::Dashed => r#" stroke-dasharray="6,4""#,
+    }
+}
+
+fn color_str(color: Option<GraphLineColor>) -> String {
+    match color {
+        Some(color) => {
+            let RGB8 { r, g, b } = color.into();
+            format!("#{r:02x}{g:02x}{b:02x}")
+        }
+        None => "currentColor".into(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        theme::Theme,
+        types::{GraphConfig, GraphLineConfig, GraphPoint, GraphSize, GraphValue},
+    };
+
+    use super::*;
+
+    fn config() -> GraphConfig {
+        let line = |ty, label: Option<&str>, color| GraphLineConfig {
+            ty: Some(ty),
+            label: label.map(Into::into),
+            color,
+            points: (0..=10)
+                .map(|x| GraphPoint {
+                    x: GraphValue::number(x as f64),
+                    y: GraphValue::number(x as f64),
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        GraphConfig {
+            width: Some(GraphSize::Dots(40)),
+            height: Some(GraphSize::Dots(20)),
+            lines: vec![
+                line(
+                    GraphType::Lines,
+                    Some("in <b>"),
+                    Some(GraphLineColor::new(255, 136, 0)),
+                ),
+                line(GraphType::Steps, None, None),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn svg(config: GraphConfig) -> String {
+        render_svg(&Plot::new(config, Theme::default()).unwrap())
+    }
+
+    #[test]
+    fn draws_each_series_in_its_color() {
+        let svg = svg(config());
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="#));
+        assert!(svg.ends_with("</svg>\n"));
+
+        let polyline = svg
+            .lines()
+            .find(|line| line.starts_with("<polyline"))
+            .unwrap();
+        assert!(polyline.ends_with(r##"fill="none" stroke="#ff8800" stroke-width="1.5"/>"##));
+        let points = polyline
+            .split('"')
+            .nth(1)
+            .unwrap()
+            .split(' ')
+            .map(|point| {
+                let (x, y) = point.split_once(',').unwrap();
+                (x.parse::<f32>().unwrap(), y.parse::<f32>().unwrap())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(points.len(), 11);
+        assert!(
+            points
+                .windows(2)
+                .all(|pair| pair[0].0 < pair[1].0 && pair[0].1 > pair[1].1)
+        );
+
+        let path = svg.lines().find(|line| line.starts_with("<path")).unwrap();
+        let palette = color_str(Some(GraphLineColor::from_palette(1)));
+        assert!(path.starts_with(r#"<path d="M57.6 136.0 V124.0 H81.6 V112.0"#));
+        assert!(path.ends_with(&format!(r#"stroke="{palette}" stroke-width="1.5"/>"#)));
+    }
+
+    #[test]
+    fn labels_the_axes_and_legend() {
+        let svg = svg(config());
+
+        assert!(svg.contains(r#"text-anchor="end" dominant-baseline="middle">10</text>"#));
+        assert!(svg.contains(r#"<text x="57.6" y="154.0" text-anchor="middle">0</text>"#));
+        assert!(svg.contains(r##"fill="#ff8800"/>"##));
+        assert!(svg.contains(">in &lt;b&gt;</text>"));
+        assert!(!svg.contains("<b>"));
+        assert!(svg.contains(">Legend</text>"));
+    }
+
+    #[test]
+    fn hides_the_series_without_figures() {
+        let svg = svg(GraphConfig {
+            no_figures: Some(true),
+            no_borders: Some(true),
+            ..config()
+        });
+
+        assert!(!svg.contains("<polyline"));
+        assert!(svg.contains(r#"dominant-baseline="middle">10</text>"#));
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use nu_protocol::LabeledError;
+
+use super::raster::{Canvas, rasterize, render_png};
+use crate::chart::Plot;
+
+const KITTY_CHUNK: usize = 4096;
+const SIXEL_COLORS: usize = 256;
+
+pub fn render_kitty(plot: &Plot) -> Result<String, LabeledError> {
+    Ok(kitty(&render_png(plot)?))
+}
+
+pub fn render_sixel(plot: &Plot) -> String {
+    sixel(&rasterize(plot))
+}
+
+fn kitty(png: &[u8]) -> String {
+    let payload = STANDARD.encode(png);
+    let chunks = payload.as_bytes().chunks(KITTY_CHUNK).collect::<Vec<_>>();
+
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let more = (i + 1 < chunks.len()) as u8;
+            let keys = match i {
+                0 => format!("a=T,f=100,m={more}"),
+                _ => format!("m={more}"),
+            };
+
+            format!("\x1b_G{keys};{}\x1b\\", String::from_utf8_lossy(chunk))
+        })
+        .collect()
+}
+
+fn sixel(canvas: &Canvas) -> String {
+    let (width, height) = (canvas.width as usize, canvas.height as usize);
+    let (palette, pixels) = quantize(&canvas.data);
+
+    let mut sixel = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+    for (i, [r, g, b]) in palette.iter().enumerate() {
+        let percent = |c: u8| (c as u32 * 100 + 127) / 255;
+        let _ = write!(
+            sixel,
+            "#{i};2;{};{};{}",
+            percent(*r),
+            percent(*g),
+            percent(*b)
+        );
+    }
+
+    for top in (0..height).step_by(6) {
+        let rows = top..(top + 6).min(height);
+        let mut colors = rows
+            .clone()
+            .flat_map(|y| &pixels[y * width..(y + 1) * width])
+            .copied()
+            .collect::<Vec<_>>();
+        colors.sort_unstable();
+        colors.dedup();
+
+        for color in colors {
+            let band = (0..width)
+                .map(|x| {
+                    let bits = rows
+                        .clone()
+                        .filter(|y| pixels[y * width + x] == color)
+                        .fold(0, |bits, y| bits | 1 << (y - top));
+                    (63 + bits) as u8 as char
+                })
+                .collect::<String>();
+
+            let _ = write!(sixel, "#{color}{}$", run_length(band.trim_end_matches('?')));
+        }
+        sixel.push('-');
+    }
+
+    sixel.push_str("\x1b\\");
+    sixel
+}
+
+fn quantize(data: &[u8]) -> (Vec<[u8; 3]>, Vec<usize>) {
+    let (colors, _) = data.as_chunks::<3>();
+    let distinct = colors.iter().collect::<HashSet<_>>().len();
+    let reduce = |[r, g, b]: [u8; 3]| match distinct > SIXEL_COLORS {
+        true => [r & 0xe0, g & 0xe0, b & 0xc0],
+        false => [r, g, b],
+    };
+
+    let mut palette = vec![];
+    let mut indices = HashMap::new();
+    let pixels = colors
+        .iter()
+        .map(|color| {
+            let color = reduce(*color);
+            *indices.entry(color).or_insert_with(|| {
+                palette.push(color);
+                palette.len() - 1
+            })
+        })
+        .collect();
+
+    (palette, pixels)
+}
+
+fn run_length(band: &str) -> String {
+    let mut encoded = String::new();
+    let mut chars = band.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let mut count = 1;
+        while chars.next_if_eq(&c).is_some() {
+            count += 1;
+        }
+
+        match count {
+            1..=3 => encoded.extend(std::iter::repeat_n(c, count)),
+            _ => {
+                let _ = write!(encoded, "!{count}{c}");
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        theme::Theme,
+        types::{GraphConfig, GraphLineConfig, GraphPoint, GraphSize, GraphValue},
+    };
+
+    const BLACK: [u8; 3] = [0, 0, 0];
+    const WHITE: [u8; 3] = [255, 255, 255];
+
+    fn canvas(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 3]) -> Canvas {
+        let pixel = &pixel;
+        Canvas {
+            width,
+            height,
+            data: (0..height)
+                .flat_map(|y| (0..width).flat_map(move |x| pixel(x, y)))
+                .collect(),
+        }
+    }
+
+    fn plot() -> Plot {
+        let mut config = GraphConfig {
+            width: Some(GraphSize::Dots(40)),
+            height: Some(GraphSize::Dots(20)),
+            ..Default::default()
+        };
+        config.lines.push(GraphLineConfig {
+            points: (0..5)
+                .map(|i| GraphPoint {
+                    x: GraphValue::number(i as f64),
+                    y: GraphValue::number((i * i) as f64),
+                })
+                .collect(),
+            ..Default::default()
+        });
+        Plot::new(config, Theme::default()).unwrap()
+    }
+
+    #[test]
+    fn sixel_encodes_bands_of_six_rows() {
+        let diagonal = canvas(3, 7, |x, y| match x == y || (x, y) == (0, 6) {
+            true => BLACK,
+            false => WHITE,
+        });
+
+        assert_eq!(
+            sixel(&diagonal),
+            "\x1bP0;1;0q\"1;1;3;7#0;2;0;0;0#1;2;100;100;100#0@AC$#1}|z$-#0@$#1?@@$-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn sixel_run_length_encodes_repeats() {
+        assert_eq!(run_length("~~~~~~??@"), "!6~??@");
+        assert_eq!(run_length("@@@A"), "@@@A");
+        assert_eq!(
+            sixel(&canvas(8, 1, |_, _| BLACK)),
+            "\x1bP0;1;0q\"1;1;8;1#0;2;0;0;0#0!8@$-\x1b\\"
+        );
+    }
+
+    #[test]
+    fn sixel_keeps_up_to_256_colors() {
+        let (palette, pixels) = quantize(&canvas(256, 1, |x, _| [x as u8, 0, 0]).data);
+
+        assert_eq!(palette.len(), 256);
+        assert_eq!(palette[200], [200, 0, 0]);
+        assert_eq!(pixels, (0..256).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sixel_reduces_to_3_3_2_above_256_colors() {
+        let colors = canvas(257, 1, |x, _| match x {
+            256 => [0, 1, 0],
+            x => [x as u8, 0, 0],
+        });
+        let (palette, pixels) = quantize(&colors.data);
+
+        assert_eq!(palette, (0..8).map(|r| [r * 32, 0, 0]).collect::<Vec<_>>());
+        assert_eq!(pixels[..256], (0..256).map(|x| x / 32).collect::<Vec<_>>());
+        assert_eq!(pixels[256], 0);
+        assert!(sixel(&colors).starts_with(concat!(
+            "\x1bP0;1;0q\"1;1;257;1",
+            "#0;2;0;0;0#1;2;13;0;0#2;2;25;0;0#3;2;38;0;0",
+            "#4;2;50;0;0#5;2;63;0;0#6;2;75;0;0#7;2;88;0;0#0",
+        )));
+    }
+
+    #[test]
+    fn sixel_frames_a_chart() {
+        let plot = plot();
+        let Canvas { width, height, .. } = rasterize(&plot);
+        let sixel = render_sixel(&plot);
+
+        assert!(sixel.starts_with(&format!("\x1bP0;1;0q\"1;1;{width};{height}#0;2;")));
+        assert!(sixel.ends_with("-\x1b\\"));
+        assert_eq!(sixel.matches('-').count(), height.div_ceil(6) as usize);
+    }
+
+    #[test]
+    fn kitty_sends_a_single_chunk() {
+        assert_eq!(kitty(&[0; 3]), "\x1b_Ga=T,f=100,m=0;AAAA\x1b\\");
+        assert_eq!(
+            kitty(&[0; 3072]),
+            format!("\x1b_Ga=T,f=100,m=0;{}\x1b\\", "A".repeat(4096))
+        );
+    }
+
+    #[test]
+    fn kitty_splits_the_payload_in_4096_byte_chunks() {
+        assert_eq!(
+            kitty(&[0; 6147]),
+            format!(
+                "\x1b_Ga=T,f=100,m=1;{}\x1b\\\x1b_Gm=1;{}\x1b\\\x1b_Gm=0;AAAA\x1b\\",
+                "A".repeat(4096),
+                "A".repeat(4096)
+            )
+        );
+    }
+
+    #[test]
+    fn kitty_frames_a_png() {
+        let kitty = render_kitty(&plot()).unwrap();
+        let payload = kitty
+            .split("\x1b\\")
+            .filter(|frame| !frame.is_empty())
+            .map(|frame| frame.split_once(';').unwrap().1)
+            .collect::<String>();
+
+        assert!(kitty.starts_with("\x1b_Ga=T,f=100,m="));
+        assert!(kitty.ends_with("\x1b\\"));
+        assert!(STANDARD.decode(payload).unwrap().starts_with(b"\x89PNG"));
+    }
+}
@@ -0,0 +1,548 @@
+use nu_ansi_term::Color;
+use rgb::RGB8;
+use textplots::{AxisBuilder, Chart, LabelBuilder, Plot as _, Shape};
+
+use crate::{
+    chart::{Plot, PlotAnnotation, PlotAnnotationKind, PlotLine},
+    types::{GraphAxisStyle, GraphLineColor, GraphType},
+    util::{pad_visible, strip_ansi, visible_width},
+};
+
+pub fn render_text(plot: &Plot) -> String {
+    let Plot {
+        width,
+        height,
+        x_axis,
+        x_range: (x_min, x_max),
+        x_style,
+        x_label,
+        y_axis,
+        y_range: (y_min, y_max),
+        y_style,
+        y_label,
+        legend,
+        no_header,
+        no_axis,
+        no_borders,
+        no_figures,
+        lines,
+        theme,
+        ..
+    } = plot;
+
+    let mut chart = Chart::new_with_y_range(*width, *height, *x_min, *x_max, *y_min, *y_max);
+
+    let fill_points = lines
+        .iter()
+        .filter(|line| line.base.is_some())
+        .map(|line| (line.color.or(theme.line), fill_points(line, plot)))
+        .collect::<Vec<_>>();
+
+    let y_axis_points = match (plot.x_axis.zero(), plot.y_axis_position(), no_axis) {
+        (None, Some(x), false) => annotation_points(
+            &PlotAnnotation {
+                kind: PlotAnnotationKind::VLine(x),
+                label: None,
+                color: None,
+                style: *y_style,
+            },
+            plot,
+        ),
+        _ => vec![],
+    };
+
+    let y2_axis_points = match (&plot.y2, no_axis) {
+        (Some(y2), false) => annotation_points(
+            &PlotAnnotation {
+                kind: PlotAnnotationKind::VLine(*x_max),
+                label: None,
+                color: None,
+                style: y2.style,
+            },
+            plot,
+        ),
+        _ => vec![],
+    };
+
+    let annotation_points = plot
+        .annotations
+        .iter()
+        .map(|annotation| (annotation.color, annotation_points(annotation, plot)))
+        .collect::<Vec<_>>();
+
+    let layers = [(None, Shape::Points(&y_axis_points))]
+        .into_iter()
+        .chain(
+            fill_points
+                .iter()
+                .map(|(color, points)| (*color, Shape::Points(points))),
+        )
+        .chain(lines.iter().map(
+            |PlotLine {
+                 ty, color, points, ..
+             }| {
+                let shape = match ty {
+                    GraphType::Points => Shape::Points(points),
+                    GraphType::Lines => Shape::Lines(points),
+                    GraphType::Steps => Shape::Steps(points),
+                    GraphType::Bars => Shape::Bars(points),
+                };
+
+                (color.or(theme.line), shape)
+            },
+        ))
+        .chain(
+            annotation_points
+                .iter()
+                .map(|(color, points)| (*color, Shape::Points(points))),
+        )
+        .chain([(None, Shape::Points(&y2_axis_points))])
+        .collect::<Vec<_>>();
+
+    let prepared_chart = layers.iter().fold(
+        chart
+            .x_axis_style((*x_style).into())
+            .y_axis_style((*y_style).into())
+            .x_label_format(x_axis.label_format())
+            .y_label_format(y_axis.label_format()),
+        |chart, (_, shape)| chart.lineplot(shape),
+    );
+
+    let get_chart_str = |chart: &mut Chart<'_>| {
+        if !no_borders {
+            chart.borders();
+        }
+
+        if !no_axis {
+            if plot.x_axis_position().is_some() {
+                chart.x_axis();
+            }
+            if plot.x_axis.zero().is_some() {
+                chart.y_axis();
+            }
+        }
+
+        if !no_figures {
+            chart.figures();
+        }
+
+        chart.to_string()
+    };
+    let chart_str = get_chart_str(prepared_chart);
+    let chart_str = match theme.colored {
+        true => paint_cells(&chart_str, &cell_colors(&layers, plot)),
+        false => chart_str,
+    };
+    let chart_str = legend.attach(
+        theme.paint_chart(&with_annotation_labels(with_ticks(chart_str, plot), plot)),
+        theme.line,
+    );
+
+    let text = (match no_header {
+        true => vec![chart_str],
+        false => vec![
+            theme
+                .label
+                .paint(match &plot.y2 {
+                    Some(y2) => format!("{y_label}/{x_label} (right: {})", y2.label),
+                    None => format!("{y_label}/{x_label}"),
+                })
+                .to_string(),
+            chart_str,
+        ],
+    })
+    .join("\n");
+
+    match theme.colored {
+        true => text,
+        false => strip_ansi(&text),
+    }
+}
+
+fn with_ticks(chart: String, plot: &Plot) -> String {
+    let mut rows = chart.lines().map(String::from).collect::<Vec<_>>();
+    if rows.pop().is_none() {
+        return chart;
+    }
+
+    let (y_min, y_max) = plot.y_range;
+    let height = plot.height as f32;
+    let mut labeled = vec![0, rows.len().saturating_sub(1)];
+    for y in &plot.y_ticks {
+        let dot = height - ((y - y_min) / (y_max - y_min) * height).round();
+        let row = dot as usize / 4;
+
+        if row < rows.len() && !labeled.contains(&row) {
+            rows[row].push_str(&format!(" {}", plot.y_axis.format(*y)));
+            labeled.push(row);
+        }
+    }
+
+    if let Some(y2) = &plot.y2 {
+        let width = rows.iter().map(|row| visible_width(row)).max().unwrap_or(0);
+        let last_row = rows.len().saturating_sub(1);
+        let mut labels = vec![(0, y2.range.1), (last_row, y2.range.0)];
+        for y in &y2.ticks {
+            let y_primary = y2.to_primary(*y, plot.y_range);
+            let dot = height - ((y_primary - y_min) / (y_max - y_min) * height).round();
+            labels.push((dot as usize / 4, *y));
+        }
+
+        let mut labeled = vec![];
+        for (row, y) in labels {
+            if row < rows.len() && !labeled.contains(&row) {
+                rows[row] = format!("{} {}", pad_visible(&rows[row], width), y2.axis.format(y));
+                labeled.push(row);
+            }
+        }
+    }
+
+    rows.push(x_figures(plot));
+    rows.push(String::new());
+    rows.join("\n")
+}
+
+fn annotation_points(
+    PlotAnnotation { kind, style, .. }: &PlotAnnotation,
+    plot: &Plot,
+) -> Vec<(f32, f32)> {
+    let (x_min, x_max) = plot.x_range;
+    let (y_min, y_max) = plot.y_range;
+    let dx = (x_max - x_min) / plot.width as f32;
+    let dy = (y_max - y_min) / plot.height as f32;
+
+    let on = |dot: u32| match style {
+        GraphAxisStyle::None => false,
+        GraphAxisStyle::Solid => true,
+        GraphAxisStyle::Dotted => dot % 3 == 0,
+        GraphAxisStyle::Dashed => dot % 4 < 2,
+    };
+
+    match *kind {
+        PlotAnnotationKind::HLine(y) if (y_min..=y_max).contains(&y) => (0..=plot.width)
+            .filter(|i| on(*i))
+            .map(|i| (x_min + i as f32 * dx, y))
+            .collect(),
+        PlotAnnotationKind::VLine(x) if (x_min..=x_max).contains(&x) => (0..=plot.height)
+            .filter(|j| on(*j))
+            .map(|j| (x, y_min + j as f32 * dy))
+            .collect(),
+        PlotAnnotationKind::Band(from, to) => (0..=plot.height)
+            .filter(|j| j % 2 == 0)
+            .map(|j| (j, y_min + j as f32 * dy))
+            .filter(|(_, y)| (from.min(to)..=from.max(to)).contains(y))
+            .flat_map(|(j, y)| {
+                (0..=plot.width)
+                    .filter(move |i| i % 4 == (j / 2 % 2) * 2)
+                    .map(move |i| (x_min + i as f32 * dx, y))
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn fill_points(line: &PlotLine, plot: &Plot) -> Vec<(f32, f32)> {
+    let (x_min, x_max) = plot.x_range;
+    let (y_min, y_max) = plot.y_range;
+    let dx = (x_max - x_min) / plot.width as f32;
+    let dy = (y_max - y_min) / plot.height as f32;
+
+    (0..=plot.width)
+        .filter_map(|i| {
+            let x = x_min + i as f32 * dx;
+            line.fill_at(x).map(|fill| (i, x, fill))
+        })
+        .flat_map(|(i, x, (from, to))| {
+            (0..=plot.height)
+                .filter(move |j| j % 2 == 0 && i % 4 == (j / 2 % 2) * 2)
+                .map(move |j| (x, y_min + j as f32 * dy))
+                .filter(move |(_, y)| (from.min(to)..=from.max(to)).contains(y))
+        })
+        .collect()
+}
+
+fn with_annotation_labels(chart: String, plot: &Plot) -> String {
+    let (x_min, x_max) = plot.x_range;
+    let (y_min, y_max) = plot.y_range;
+    let columns = plot.width as usize / 2 + 1;
+    let mut rows = chart.split('\n').map(String::from).collect::<Vec<_>>();
+    let last_row = (plot.height as usize / 4).min(rows.len().saturating_sub(1));
+
+    let column = |x: f32| ((x - x_min) / (x_max - x_min) * plot.width as f32).round() as usize / 2;
+    let row = |y: f32| {
+        let dot = plot.height as f32 - ((y - y_min) / (y_max - y_min) * plot.height as f32).round();
+        (dot as usize / 4).min(last_row)
+    };
+
+    for PlotAnnotation {
+        kind, label, color, ..
+    } in &plot.annotations
+    {
+        let Some(label) = label else {
+            continue;
+        };
+
+        let (x, y, row, start) = match *kind {
+            PlotAnnotationKind::HLine(y) => (
+                x_max,
+                y,
+                row(y),
+                columns.saturating_sub(label.chars().count() + 1),
+            ),
+            PlotAnnotationKind::VLine(x) => (x, y_max, 1.min(last_row), column(x) + 1),
+            PlotAnnotationKind::Band(from, to) => {
+                let top = from.max(to).min(y_max);
+                (x_min, top, row(top), 1)
+            }
+            PlotAnnotationKind::Text(x, y) => (x, y, row(y), column(x)),
+        };
+        if !(x_min..=x_max).contains(&x) || !(y_min..=y_max).contains(&y) {
+            continue;
+        }
+
+        let label = label
+            .chars()
+            .take(columns.saturating_sub(start))
+            .map(|c| paint(c, *color))
+            .collect::<Vec<_>>();
+        rows[row] = overlay(&rows[row], start, &label);
+    }
+
+    rows.join("\n")
+}
+
+fn overlay(row: &str, start: usize, text: &[String]) -> String {
+    let mut cells = Vec::<String>::new();
+    let mut escape = String::new();
+    let mut chars = row.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => {
+                let mut sequence = String::from(c);
+                for c in chars.by_ref() {
+                    sequence.push(c);
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+
+                match (sequence.as_str(), cells.last_mut()) {
+                    ("\x1b[0m", Some(cell)) if escape.is_empty() => cell.push_str(&sequence),
+                    _ => escape.push_str(&sequence),
+                }
+            }
+            c => {
+                cells.push(format!("{escape}{c}"));
+                escape.clear();
+            }
+        }
+    }
+
+    if cells.len() < start + text.len() {
+        cells.resize(start + text.len(), " ".into());
+    }
+    cells[start..start + text.len()].clone_from_slice(text);
+
+    format!("{}{escape}", cells.concat())
+}
+
+fn cell_colors(
+    layers: &[(Option<GraphLineColor>, Shape)],
+    plot: &Plot,
+) -> Vec<Vec<Option<GraphLineColor>>> {
+    let (x_min, x_max) = plot.x_range;
+    let (y_min, y_max) = plot.y_range;
+    let mut colors = vec![];
+
+    for (color, shape) in layers {
+        let mut chart =
+            Chart::new_with_y_range(plot.width, plot.height, x_min, x_max, y_min, y_max);
+        let chart = chart.lineplot(shape);
+        chart.figures();
+
+        for (row, cells) in chart.frame().split('\n').enumerate() {
+            if colors.len() <= row {
+                colors.push(vec![]);
+            }
+
+            for (column, cell) in cells.chars().enumerate() {
+                if colors[row].len() <= column {
+                    colors[row].resize(column + 1, None);
+                }
+                if !matches!(cell, ' ' | '\u{2800}') {
+                    colors[row][column] = *color;
+                }
+            }
+        }
+    }
+
+    colors
+}
+
+fn paint_cells(chart: &str, colors: &[Vec<Option<GraphLineColor>>]) -> String {
+    chart
+        .split('\n')
+        .enumerate()
+        .map(|(row, line)| match colors.get(row) {
+            Some(colors) => line
+                .chars()
+                .enumerate()
+                .map(|(column, c)| paint(c, colors.get(column).copied().flatten()))
+                .collect(),
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn paint(c: char, color: Option<GraphLineColor>) -> String {
+    match color {
+        Some(color) => {
+            let RGB8 { r, g, b } = color.into();
+            Color::Rgb(r, g, b).paint(c.to_string()).to_string()
+        }
+        None => c.to_string(),
+    }
+}
+
+fn x_figures(plot: &Plot) -> String {
+    let (x_min, x_max) = plot.x_range;
+    let width = plot.width as usize / 2;
+    let mut line = vec![' '; width];
+    let mut placed = vec![];
+
+    let mut place = |label: String, start: usize| {
+        let label = label.chars().collect::<Vec<_>>();
+        let end = start + label.len();
+        if placed
+            .iter()
+            .any(|(other_start, other_end)| start <= *other_end && *other_start <= end)
+        {
+            return;
+        }
+
+        if line.len() < end {
+            line.resize(end, ' ');
+        }
+        line[start..end].copy_from_slice(&label);
+        placed.push((start, end));
+    };
+
+    let max = plot.x_axis.format(x_max);
+    let max_start = width.saturating_sub(max.chars().count());
+    place(plot.x_axis.format(x_min), 0);
+    place(max, max_start);
+
+    for x in &plot.x_ticks {
+        let label = plot.x_axis.format(*x);
+        let column = ((x - x_min) / (x_max - x_min) * plot.width as f32).round() as usize / 2;
+        place(
+            label.clone(),
+            column
+                .saturating_sub(label.chars().count() / 2)
+                .min(max_start),
+        );
+    }
+
+    line.into_iter().collect::<String>().trim_end().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        theme::Theme,
+        types::{
+            GraphAnnotation, GraphAnnotationType, GraphConfig, GraphLineConfig, GraphPoint,
+            GraphSize, GraphValue, GraphYAxis,
+        },
+    };
+
+    use super::*;
+
+    fn two_axes(no_figures: bool) -> String {
+        let line = |axis, scale: f64| GraphLineConfig {
+            axis: Some(axis),
+            points: (0..10)
+                .map(|x| GraphPoint {
+                    x: GraphValue::number(x as f64),
+                    y: GraphValue::number(x as f64 * scale),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let config = GraphConfig {
+            width: Some(GraphSize::Dots(40)),
+            height: Some(GraphSize::Dots(20)),
+            no_header: Some(true),
+            no_figures: Some(no_figures),
+            lines: vec![line(GraphYAxis::Left, 1.0), line(GraphYAxis::Right, 100.0)],
+            ..Default::default()
+        };
+
+        strip_ansi(&render_text(&Plot::new(config, Theme::default()).unwrap()))
+    }
+
+    #[test]
+    fn labels_both_y_axes_with_or_without_figures() {
+        let labels = |chart: &str| {
+            chart
+                .lines()
+                .map(|row| {
+                    row.trim_start_matches(|c| ('\u{2800}'..='\u{28ff}').contains(&c))
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let with_figures = two_axes(false);
+        let without_figures = two_axes(true);
+        assert!(with_figures.lines().next().unwrap().ends_with(" 10  1000"));
+        assert_eq!(labels(&with_figures), labels(&without_figures));
+        assert_ne!(with_figures, without_figures);
+    }
+
+    #[test]
+    fn labels_annotations_where_they_are_drawn() {
+        let annotation = |ty, x: Option<f64>, y: Option<f64>, label: &str| GraphAnnotation {
+            ty,
+            x: x.map(GraphValue::number),
+            y: y.map(GraphValue::number),
+            from: None,
+            to: None,
+            label: Some(label.into()),
+            color: None,
+            style: None,
+        };
+        let config = GraphConfig {
+            width: Some(GraphSize::Dots(40)),
+            height: Some(GraphSize::Dots(20)),
+            no_header: Some(true),
+            lines: vec![GraphLineConfig {
+                points: (0..=10)
+                    .map(|x| GraphPoint {
+                        x: GraphValue::number(x as f64),
+                        y: GraphValue::number(x as f64),
+                    })
+                    .collect(),
+                ..Default::default()
+            }],
+            annotations: vec![
+                annotation(GraphAnnotationType::Hline, None, Some(5.0), "limit"),
+                annotation(GraphAnnotationType::Text, Some(2.0), Some(8.0), "peak"),
+                annotation(GraphAnnotationType::Vline, Some(6.0), None, "v"),
+            ],
+            ..Default::default()
+        };
+        let theme = Theme {
+            colored: false,
+            ..Default::default()
+        };
+        let text = render_text(&Plot::new(config, theme).unwrap());
+        let rows = text.lines().collect::<Vec<_>>();
+
+        assert_eq!(rows[1], "⡇⠀⠀⠀peak⠀⠀⠀⠀⡁v⠄⠀⠁⠀⠀⠀⠄ 7.5");
+        assert_eq!(rows[2], "⡧⠀⠤⠀⠤⠀⠤⠀⠤⠀⠤⠀⡥⠀⠤limit⠆ 5");
+        assert_eq!(rows[4], "⡇⠀⠄⠀⠁⠀⠀⠀⠀⠀⠀⠀⡁⠀⠀⠀⠀⠀⠀⠀⠄");
+    }
+}
@@ -0,0 +1,214 @@
+use nu_protocol::LabeledError;
+
+use crate::types::{GraphBin, GraphBinning, GraphBoxStats, GraphUnit, GraphValue};
+
+const MAX_BINS: usize = 10_000;
+
+pub fn sorted_finite(values: &[GraphValue]) -> Vec<f64> {
+    let mut values = values
+        .iter()
+        .map(|value| value.value)
+        .filter(|value| value.is_finite())
+        .collect::<Vec<_>>();
+    values.sort_by(f64::total_cmp);
+    values
+}
+
+pub fn quantile(sorted: &[f64], q: f64) -> f64 {
+    match sorted.len() {
+        0 => f64::NAN,
+        1 => sorted[0],
+        len => {
+            let position = q.clamp(0.0, 1.0) * (len - 1) as f64;
+            let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+            sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+        }
+    }
+}
+
+pub fn unit_of(values: &[GraphValue]) -> Result<GraphUnit, LabeledError> {
+    values
+        .iter()
+        .try_fold(GraphUnit::Number, |unit, value| match (unit, value.unit) {
+            (unit, GraphUnit::Number) => Ok(unit),
+            (GraphUnit::Number, value_unit) => Ok(value_unit),
+            (unit, value_unit) if unit == value_unit => Ok(unit),
+            (unit, value_unit) => Err(LabeledError::new("Mixed units in the values").with_help(
+                format!("got both {} and {} values", unit.name(), value_unit.name()),
+            )),
+        })
+}
+
+pub fn bins(values: &[GraphValue], binning: GraphBinning) -> Result<Vec<GraphBin>, LabeledError> {
+    let unit = unit_of(values)?;
+    let sorted = sorted_finite(values);
+
+    let (Some(&min), Some(&max)) = (sorted.first(), sorted.last()) else {
+        return Ok(vec![]);
+    };
+
+    let range = max - min;
+    let (min, count, width) = match range > 0.0 {
+        false => (min - 0.5, 1, 1.0),
+        true => {
+            let too_many = |count: f64| {
+                LabeledError::new("Too many bins").with_help(format!(
+                    "got {count} bins, at most {MAX_BINS} can be drawn, use fewer bins or a wider bin width"
+                ))
+            };
+
+            let count = match binning {
+                GraphBinning::Count(count) if count > MAX_BINS as i64 => {
+                    return Err(too_many(count as f64));
+                }
+                GraphBinning::Count(count) if count > 0 => count as usize,
+                GraphBinning::Count(_) => {
+                    return Err(LabeledError::new("Invalid bin count")
+                        .with_help("the number of bins should be at least 1"));
+                }
+                GraphBinning::Width(width) if width > 0.0 => match (range / width).ceil() {
+                    count if count > MAX_BINS as f64 => return Err(too_many(count)),
+                    count => count as usize,
+                },
+                GraphBinning::Width(_) => {
+                    return Err(LabeledError::new("Invalid bin width")
+                        .with_help("the width of a bin should be greater than 0"));
+                }
+                GraphBinning::Sturges => sturges(sorted.len()),
+                GraphBinning::FreedmanDiaconis => {
+                    let iqr = quantile(&sorted, 0.75) - quantile(&sorted, 0.25);
+                    let width = 2.0 * iqr / (sorted.len() as f64).cbrt();
+
+                    match (range / width).ceil() {
+                        count if width > 0.0 && count <= MAX_BINS as f64 => count as usize,
+                        _ => sturges(sorted.len()),
+                    }
+                }
+            }
+            .max(1);
+
+            let width = match binning {
+                GraphBinning::Width(width) => width,
+                _ => range / count as f64,
+            };
+
+            (min, count, width)
+        }
+    };
+
+    let mut counts = vec![0i64; count];
+    for value in sorted {
+        let i = ((value - min) / width).floor() as usize;
+        counts[i.min(count - 1)] += 1;
+    }
+
+    Ok(counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| GraphBin {
+            start: GraphValue {
+                value: min + width * i as f64,
+                unit,
+            },
+            end: GraphValue {
+                value: min + width * (i + 1) as f64,
+                unit,
+            },
+            count,
+        })
+        .collect())
+}
+
+pub fn box_stats(
+    label: String,
+    values: &[GraphValue],
+) -> Result<Option<GraphBoxStats>, LabeledError> {
+    let unit = unit_of(values)?;
+    let sorted = sorted_finite(values);
+    let value = |value: f64| GraphValue { value, unit };
+
+    let (Some(&min), Some(&max)) = (sorted.first(), sorted.last()) else {
+        return Ok(None);
+    };
+    let (q1, q3) = (quantile(&sorted, 0.25), quantile(&sorted, 0.75));
+    let (low, high) = (q1 - 1.5 * (q3 - q1), q3 + 1.5 * (q3 - q1));
+    let inside = || sorted.iter().copied().filter(|v| (low..=high).contains(v));
+
+    Ok(Some(GraphBoxStats {
+        label,
+        count: sorted.len() as i64,
+        min: value(min),
+        lower_whisker: value(inside().next().unwrap_or(q1)),
+        q1: value(q1),
+        median: value(quantile(&sorted, 0.5)),
+        mean: value(sorted.iter().sum::<f64>() / sorted.len() as f64),
+        q3: value(q3),
+        upper_whisker: value(inside().next_back().unwrap_or(q3)),
+        max: value(max),
+        outliers: sorted
+            .iter()
+            .filter(|v| !(low..=high).contains(*v))
+            .map(|v| value(*v))
+            .collect(),
+    }))
+}
+
+fn sturges(len: usize) -> usize {
+    (len as f64).log2().ceil() as usize + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(values: impl IntoIterator<Item = f64>) -> Vec<GraphValue> {
+        values.into_iter().map(GraphValue::number).collect()
+    }
+
+    #[test]
+    fn rejects_too_many_bins_from_a_tiny_width() {
+        let values = numbers([0.0, 1000.0]);
+        assert!(bins(&values, GraphBinning::Width(1e-9)).is_err());
+    }
+
+    #[test]
+    fn rejects_too_many_bins_from_a_huge_count() {
+        let values = numbers([0.0, 1000.0]);
+        assert!(bins(&values, GraphBinning::Count(1_000_000_000_000)).is_err());
+        assert_eq!(
+            bins(&values, GraphBinning::Count(MAX_BINS as i64))
+                .unwrap()
+                .len(),
+            MAX_BINS
+        );
+    }
+
+    #[test]
+    fn freedman_diaconis_falls_back_to_sturges_on_heavy_tails() {
+        let values = numbers((0..100).map(|i| 1.0 + i as f64 * 1e-9).chain([1e9]));
+        let bins = bins(&values, GraphBinning::FreedmanDiaconis).unwrap();
+        assert_eq!(bins.len(), sturges(values.len()));
+        assert_eq!(bins.iter().map(|bin| bin.count).sum::<i64>(), 101);
+    }
+
+    #[test]
+    fn rejects_mixed_units() {
+        let value = |value, unit| GraphValue { value, unit };
+        let values = [
+            value(1024.0, GraphUnit::Filesize),
+            value(1e9, GraphUnit::Duration),
+        ];
+
+        let error = unit_of(&values).unwrap_err();
+        assert_eq!(error.msg, "Mixed units in the values");
+        assert!(bins(&values, GraphBinning::Sturges).is_err());
+        assert!(box_stats("mixed".into(), &values).is_err());
+
+        let values = [
+            value(1.0, GraphUnit::Number),
+            value(1e9, GraphUnit::Duration),
+        ];
+        assert_eq!(unit_of(&values).unwrap(), GraphUnit::Duration);
+        assert_eq!(unit_of(&[]).unwrap(), GraphUnit::Number);
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use nu_ansi_term::{Color, Style};
+use nu_protocol::Value;
+use rgb::RGB8;
+
+use crate::{color::parse_color, types::GraphLineColor};
+
+#[derive(Clone)]
+pub struct Theme {
+    pub colored: bool,
+    pub border: Style,
+    pub label: Style,
+    pub line: Option<GraphLineColor>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            colored: true,
+            border: Style::new(),
+            label: Style::new(),
+            line: None,
+        }
+    }
+}
+
+impl Theme {
+    pub fn new(color_config: &HashMap<String, Value>, colored: bool) -> Self {
+        let color = |key: &str| color_config.get(key).and_then(config_color);
+        let style = |key: &str| {
+            color(key)
+                .map(|color| {
+                    let RGB8 { r, g, b } = color.into();
+                    Style::new().fg(Color::Rgb(r, g, b))
+                })
+                .unwrap_or_default()
+        };
+
+        Self {
+            colored,
+            border: style("separator"),
+            label: style("header"),
+            line: color("float"),
+        }
+    }
+
+    pub fn paint_chart(&self, chart: &str) -> String {
+        chart
+            .split('\n')
+            .map(|line| self.paint_chart_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn paint_chart_line(&self, line: &str) -> String {
+        let mut painted = String::new();
+        let mut segment = String::new();
+        let mut segment_style = None;
+        let mut in_color = false;
+        let mut chars = line.chars();
+
+        let flush = |painted: &mut String, segment: &mut String, style: Option<Style>| {
+            if let Some(style) = style {
+                painted.push_str(&style.paint(segment.as_str()).to_string());
+            }
+            segment.clear();
+        };
+
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                flush(&mut painted, &mut segment, segment_style.take());
+
+                let mut escape = String::from(c);
+                for c in chars.by_ref() {
+                    escape.push(c);
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+
+                in_color = escape != "\x1b[0m";
+                painted.push_str(&escape);
+                continue;
+            }
+
+            if in_color {
+                painted.push(c);
+                continue;
+            }
+
+            let style = match c {
+                '\u{2800}'..='\u{28ff}' => self.border,
+                _ => self.label,
+            };
+            if segment_style != Some(style) {
+                flush(&mut painted, &mut segment, segment_style.replace(style));
+            }
+            segment.push(c);
+        }
+        flush(&mut painted, &mut segment, segment_style);
+
+        painted
+    }
+}
+
+fn config_color(value: &Value) -> Option<GraphLineColor> {
+    match value {
+        Value::String { val, .. } => parse_color(val),
+        Value::Record { val, .. } => val.get("fg").and_then(config_color),
+        _ => None,
+    }
+}
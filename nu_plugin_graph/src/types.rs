@@ -1,36 +1,67 @@
-use nu_protocol::{FromValue, IntoValue};
+// the closures generated by the FromValue derives return a bare ShellError, and attributes on
+// the derived types don't reach the generated impls
+#![allow(clippy::result_large_err)]
+
+use chrono::DateTime;
+use nu_protocol::{FromValue, IntoValue, ShellError, Span, Type, Value, engine::Closure};
 use rgb::RGB8;
 use smart_default::SmartDefault;
 use textplots::LineStyle;
 
-#[derive(Debug, Clone, FromValue, IntoValue)]
+use crate::color::parse_color;
+
+#[derive(Debug, Default, Clone, FromValue, IntoValue)]
 #[nu_value(type_name = "graph-config")]
 pub struct GraphConfig {
     #[nu_value(rename = "type")]
     pub ty: Option<GraphType>,
+    pub stacking: Option<GraphStacking>,
 
-    pub width: Option<u32>,
-    pub height: Option<u32>,
+    pub width: Option<GraphSize>,
+    pub height: Option<GraphSize>,
 
-    pub x_min: Option<f32>,
-    pub x_max: Option<f32>,
+    pub x_min: Option<GraphValue>,
+    pub x_max: Option<GraphValue>,
     pub x_style: Option<GraphAxisStyle>,
     pub x_label: Option<String>,
+    pub x_scale: Option<GraphScale>,
+    pub x_padding: Option<f64>,
+    pub x_include_zero: Option<bool>,
+    pub x_ticks: Option<u32>,
 
-    pub y_min: Option<f32>,
-    pub y_max: Option<f32>,
+    pub y_min: Option<GraphValue>,
+    pub y_max: Option<GraphValue>,
     pub y_style: Option<GraphAxisStyle>,
     pub y_label: Option<String>,
+    pub y_scale: Option<GraphScale>,
+    pub y_padding: Option<f64>,
+    pub y_include_zero: Option<bool>,
+    pub y_ticks: Option<u32>,
+
+    pub y2_min: Option<GraphValue>,
+    pub y2_max: Option<GraphValue>,
+    pub y2_style: Option<GraphAxisStyle>,
+    pub y2_label: Option<String>,
+    pub y2_scale: Option<GraphScale>,
+
+    pub palette: Option<GraphPalette>,
+
+    pub legend: Option<bool>,
+    pub legend_position: Option<GraphLegendPosition>,
 
     pub no_header: Option<bool>,
     pub no_axis: Option<bool>,
     pub no_borders: Option<bool>,
     pub no_figures: Option<bool>,
+    pub no_downsample: Option<bool>,
 
+    #[nu_value(default)]
     pub lines: Vec<GraphLineConfig>,
+    #[nu_value(default)]
+    pub annotations: Vec<GraphAnnotation>,
 }
 
-#[derive(Debug, Clone, SmartDefault, FromValue, IntoValue)]
+#[derive(Debug, Clone, Copy, SmartDefault, FromValue, IntoValue)]
 #[nu_value(type_name = "graph-type")]
 pub enum GraphType {
     #[default]
@@ -43,8 +74,114 @@ pub enum GraphType {
 #[derive(Debug, SmartDefault, Clone, FromValue, IntoValue)]
 #[nu_value(type_name = "graph-line-config")]
 pub struct GraphLineConfig {
+    #[nu_value(rename = "type")]
+    pub ty: Option<GraphType>,
+    pub label: Option<String>,
     pub color: Option<GraphLineColor>,
+    pub axis: Option<GraphYAxis>,
+    #[nu_value(default)]
     pub points: Vec<GraphPoint>,
+    pub function: Option<Closure>,
+}
+
+#[derive(Debug, Clone, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-annotation")]
+pub struct GraphAnnotation {
+    #[nu_value(rename = "type")]
+    pub ty: GraphAnnotationType,
+    pub x: Option<GraphValue>,
+    pub y: Option<GraphValue>,
+    pub from: Option<GraphValue>,
+    pub to: Option<GraphValue>,
+    pub label: Option<String>,
+    pub color: Option<GraphLineColor>,
+    pub style: Option<GraphAxisStyle>,
+}
+
+#[derive(Debug, Clone, Copy, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-annotation-type")]
+pub enum GraphAnnotationType {
+    Hline,
+    Vline,
+    Band,
+    Text,
+}
+
+impl GraphAnnotationType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GraphAnnotationType::Hline => "hline",
+            GraphAnnotationType::Vline => "vline",
+            GraphAnnotationType::Band => "band",
+            GraphAnnotationType::Text => "text",
+        }
+    }
+}
+
+#[derive(Debug, SmartDefault, Clone, Copy, PartialEq, Eq, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-stacking")]
+pub enum GraphStacking {
+    #[default]
+    None,
+    Stacked,
+    Percent,
+}
+
+#[derive(Debug, SmartDefault, Clone, Copy, PartialEq, Eq, Hash, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-y-axis")]
+pub enum GraphYAxis {
+    #[default]
+    Left,
+    Right,
+}
+
+#[derive(Debug, SmartDefault, Clone, Copy, PartialEq, Eq, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-scale")]
+pub enum GraphScale {
+    #[default]
+    Linear,
+    Log10,
+    Log2,
+    Symlog,
+}
+
+impl GraphScale {
+    pub fn apply(self, value: f64) -> f64 {
+        match self {
+            GraphScale::Linear => value,
+            GraphScale::Log10 => value.log10(),
+            GraphScale::Log2 => value.log2(),
+            GraphScale::Symlog => value.signum() * value.abs().ln_1p() / std::f64::consts::LN_10,
+        }
+    }
+
+    pub fn invert(self, value: f64) -> f64 {
+        match self {
+            GraphScale::Linear => value,
+            GraphScale::Log10 => 10f64.powf(value),
+            GraphScale::Log2 => value.exp2(),
+            GraphScale::Symlog => value.signum() * (value.abs() * std::f64::consts::LN_10).exp_m1(),
+        }
+    }
+}
+
+#[derive(Debug, SmartDefault, Clone, Copy, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-format")]
+pub enum GraphFormat {
+    #[default]
+    Text,
+    Svg,
+    Png,
+    Sixel,
+    Kitty,
+}
+
+#[derive(Debug, SmartDefault, Clone, Copy, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-legend-position")]
+pub enum GraphLegendPosition {
+    #[default]
+    Bottom,
+    Right,
 }
 
 #[derive(Debug, SmartDefault, Clone, Copy, FromValue, IntoValue)]
@@ -68,14 +205,208 @@ impl From<GraphAxisStyle> for LineStyle {
     }
 }
 
-#[derive(Debug, Clone, Copy, FromValue, IntoValue)]
-#[nu_value(type_name = "graph-line-color")]
+#[derive(Debug, Default, Clone, Copy, IntoValue)]
 pub struct GraphLineColor {
     pub r: u16,
     pub g: u16,
     pub b: u16,
 }
 
+impl GraphLineColor {
+    pub const PALETTE: [Self; 10] = [
+        Self::new(78, 121, 167),
+        Self::new(242, 142, 43),
+        Self::new(225, 87, 89),
+        Self::new(118, 183, 178),
+        Self::new(89, 161, 79),
+        Self::new(237, 201, 72),
+        Self::new(176, 122, 161),
+        Self::new(255, 157, 167),
+        Self::new(156, 117, 95),
+        Self::new(186, 176, 172),
+    ];
+
+    pub const fn new(r: u16, g: u16, b: u16) -> Self {
+        Self { r, g, b }
+    }
+
+    pub fn from_palette(i: usize) -> Self {
+        Self::PALETTE[i % Self::PALETTE.len()]
+    }
+
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let lerp = |a: u16, b: u16| (a as f32 + (b as f32 - a as f32) * t).round() as u16;
+
+        Self::new(
+            lerp(self.r, other.r),
+            lerp(self.g, other.g),
+            lerp(self.b, other.b),
+        )
+    }
+}
+
+impl FromValue for GraphLineColor {
+    fn from_value(v: Value) -> Result<Self, ShellError> {
+        #[derive(FromValue)]
+        struct Rgb {
+            r: u16,
+            g: u16,
+            b: u16,
+        }
+
+        match v {
+            Value::String { ref val, .. } => {
+                parse_color(val).ok_or_else(|| ShellError::CantConvert {
+                    to_type: Self::expected_type().to_string(),
+                    from_type: format!("string '{val}'"),
+                    span: v.span(),
+                    help: Some(
+                        "expected a hex color like '#ff8800', a CSS or ANSI color name, or a {r, g, b} record"
+                            .into(),
+                    ),
+                })
+            }
+            v => Rgb::from_value(v).map(|Rgb { r, g, b }| Self::new(r, g, b)),
+        }
+    }
+
+    fn expected_type() -> Type {
+        Type::Custom("graph-line-color".into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, SmartDefault, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-palette")]
+pub enum GraphPalette {
+    #[default]
+    Tableau10,
+    Viridis,
+    Magma,
+    None,
+}
+
+impl GraphPalette {
+    pub fn color(self, i: usize, count: usize) -> Option<GraphLineColor> {
+        let gradient = |preset: GraphGradientPreset| {
+            let level = match count > 1 {
+                true => i as f32 / (count - 1) as f32,
+                false => 0.0,
+            };
+
+            GraphGradient::from(preset).at(level)
+        };
+
+        match self {
+            GraphPalette::Tableau10 => Some(GraphLineColor::from_palette(i)),
+            GraphPalette::Viridis => Some(gradient(GraphGradientPreset::Viridis)),
+            GraphPalette::Magma => Some(gradient(GraphGradientPreset::Magma)),
+            GraphPalette::None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, SmartDefault, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-gradient-preset")]
+pub enum GraphGradientPreset {
+    #[default]
+    Viridis,
+    Magma,
+    Heat,
+    Blues,
+    Greys,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphGradient {
+    pub stops: Vec<GraphLineColor>,
+}
+
+impl GraphGradient {
+    pub fn at(&self, level: f32) -> GraphLineColor {
+        let last = self.stops.len().saturating_sub(1);
+        if last == 0 {
+            return self.stops.first().copied().unwrap_or_default();
+        }
+
+        let position = level.clamp(0.0, 1.0) * last as f32;
+        let i = (position.floor() as usize).min(last - 1);
+
+        self.stops[i].lerp(self.stops[i + 1], position - i as f32)
+    }
+}
+
+impl From<GraphGradientPreset> for GraphGradient {
+    fn from(preset: GraphGradientPreset) -> Self {
+        let stops = match preset {
+            GraphGradientPreset::Viridis => vec![
+                GraphLineColor::new(68, 1, 84),
+                GraphLineColor::new(59, 82, 139),
+                GraphLineColor::new(33, 145, 140),
+                GraphLineColor::new(94, 201, 98),
+                GraphLineColor::new(253, 231, 37),
+            ],
+            GraphGradientPreset::Magma => vec![
+                GraphLineColor::new(0, 0, 4),
+                GraphLineColor::new(81, 18, 124),
+                GraphLineColor::new(183, 55, 121),
+                GraphLineColor::new(252, 137, 97),
+                GraphLineColor::new(252, 253, 191),
+            ],
+            GraphGradientPreset::Heat => vec![
+                GraphLineColor::new(89, 161, 79),
+                GraphLineColor::new(237, 201, 72),
+                GraphLineColor::new(225, 87, 89),
+            ],
+            GraphGradientPreset::Blues => vec![
+                GraphLineColor::new(247, 251, 255),
+                GraphLineColor::new(107, 174, 214),
+                GraphLineColor::new(8, 48, 107),
+            ],
+            GraphGradientPreset::Greys => vec![
+                GraphLineColor::new(240, 240, 240),
+                GraphLineColor::new(37, 37, 37),
+            ],
+        };
+
+        Self { stops }
+    }
+}
+
+impl FromValue for GraphGradient {
+    fn from_value(v: Value) -> Result<Self, ShellError> {
+        match v {
+            Value::String { .. } => GraphGradientPreset::from_value(v).map(Into::into),
+            Value::List { .. } => {
+                let span = v.span();
+                let stops = Vec::<GraphLineColor>::from_value(v)?;
+
+                match stops.is_empty() {
+                    true => Err(ShellError::CantConvert {
+                        to_type: Self::expected_type().to_string(),
+                        from_type: "empty list".into(),
+                        span,
+                        help: Some("a gradient needs at least one color".into()),
+                    }),
+                    false => Ok(Self { stops }),
+                }
+            }
+            v => Err(ShellError::CantConvert {
+                to_type: Self::expected_type().to_string(),
+                from_type: v.get_type().to_string(),
+                span: v.span(),
+                help: Some(
+                    "expected a preset name ([viridis*, magma, heat, blues, greys]) or a list of colors"
+                        .into(),
+                ),
+            }),
+        }
+    }
+
+    fn expected_type() -> Type {
+        Type::Custom("graph-gradient".into())
+    }
+}
+
 impl From<GraphLineColor> for RGB8 {
     fn from(GraphLineColor { r, g, b }: GraphLineColor) -> Self {
         RGB8 {
@@ -86,9 +417,209 @@ impl From<GraphLineColor> for RGB8 {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphSize {
+    Dots(u32),
+    Fraction(f64),
+}
+
+impl GraphSize {
+    pub fn resolve(size: Option<Self>, available: u32) -> u32 {
+        match size {
+            Some(GraphSize::Dots(dots)) => dots,
+            Some(GraphSize::Fraction(fraction)) => (available as f64 * fraction).round() as u32,
+            None => available,
+        }
+    }
+}
+
+impl FromValue for GraphSize {
+    fn from_value(v: Value) -> Result<Self, ShellError> {
+        match v {
+            Value::Int { val, .. } if (0..=u32::MAX as i64).contains(&val) => {
+                Ok(Self::Dots(val as u32))
+            }
+            Value::Float { val, .. } if val > 0.0 && val <= 1.0 => Ok(Self::Fraction(val)),
+            Value::Float { val, .. } if val > 1.0 && val.round() <= u32::MAX as f64 => {
+                Ok(Self::Dots(val.round() as u32))
+            }
+            v => Err(ShellError::CantConvert {
+                to_type: Self::expected_type().to_string(),
+                from_type: v.get_type().to_string(),
+                span: v.span(),
+                help: Some(
+                    "expected a size in dots, or a fraction of the terminal when between 0 and 1"
+                        .into(),
+                ),
+            }),
+        }
+    }
+
+    fn expected_type() -> Type {
+        Type::Custom("graph-size".into())
+    }
+}
+
+impl IntoValue for GraphSize {
+    fn into_value(self, span: Span) -> Value {
+        match self {
+            GraphSize::Dots(dots) => Value::int(dots as i64, span),
+            GraphSize::Fraction(fraction) => Value::float(fraction, span),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, FromValue, IntoValue)]
 #[nu_value(type_name = "graph-point")]
 pub struct GraphPoint {
-    pub x: f32,
-    pub y: f32,
+    pub x: GraphValue,
+    pub y: GraphValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SmartDefault)]
+pub enum GraphUnit {
+    #[default]
+    Number,
+    Date,
+    Duration,
+    Filesize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphValue {
+    pub value: f64,
+    pub unit: GraphUnit,
+}
+
+impl GraphValue {
+    pub const fn number(value: f64) -> Self {
+        Self {
+            value,
+            unit: GraphUnit::Number,
+        }
+    }
+
+    pub fn try_from_value(value: &Value) -> Result<Option<Self>, ShellError> {
+        let (value, unit) = match value {
+            Value::Int { val, .. } => (*val as f64, GraphUnit::Number),
+            Value::Float { val, .. } => (*val, GraphUnit::Number),
+            Value::Date { val, .. } => match val.timestamp_nanos_opt() {
+                Some(nanos) => (nanos as f64, GraphUnit::Date),
+                None => {
+                    return Err(ShellError::CantConvert {
+                        to_type: Self::expected_type().to_string(),
+                        from_type: value.get_type().to_string(),
+                        span: value.span(),
+                        help: Some("only dates between 1677 and 2262 can be drawn".into()),
+                    });
+                }
+            },
+            Value::Duration { val, .. } => (*val as f64, GraphUnit::Duration),
+            Value::Filesize { val, .. } => (val.get() as f64, GraphUnit::Filesize),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Self { value, unit }))
+    }
+}
+
+impl FromValue for GraphValue {
+    fn from_value(v: Value) -> Result<Self, ShellError> {
+        Self::try_from_value(&v)?.ok_or_else(|| ShellError::CantConvert {
+            to_type: Self::expected_type().to_string(),
+            from_type: v.get_type().to_string(),
+            span: v.span(),
+            help: Some("expected a number, datetime, duration or filesize".into()),
+        })
+    }
+
+    fn expected_type() -> Type {
+        Type::Custom("graph-value".into())
+    }
+}
+
+impl IntoValue for GraphValue {
+    fn into_value(self, span: Span) -> Value {
+        let GraphValue { value, unit } = self;
+
+        match unit {
+            GraphUnit::Number => Value::float(value, span),
+            GraphUnit::Date => {
+                Value::date(DateTime::from_timestamp_nanos(value as i64).into(), span)
+            }
+            GraphUnit::Duration => Value::duration(value as i64, span),
+            GraphUnit::Filesize => Value::filesize(value as i64, span),
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-bar")]
+pub struct GraphBar {
+    pub label: String,
+    pub value: GraphValue,
+    pub color: Option<GraphLineColor>,
+}
+
+#[derive(Debug, Clone, Copy, SmartDefault, FromValue, IntoValue)]
+#[nu_value(type_name = "graph-bar-sort")]
+pub enum GraphBarSort {
+    #[default]
+    None,
+    Asc,
+    Desc,
+    Label,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum GraphBinning {
+    Count(i64),
+    Width(f64),
+    Sturges,
+    FreedmanDiaconis,
+}
+
+#[derive(Debug, Clone, Copy, IntoValue)]
+pub struct GraphBin {
+    pub start: GraphValue,
+    pub end: GraphValue,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, IntoValue)]
+pub struct GraphBoxStats {
+    pub label: String,
+    pub count: i64,
+    pub min: GraphValue,
+    pub lower_whisker: GraphValue,
+    pub q1: GraphValue,
+    pub median: GraphValue,
+    pub mean: GraphValue,
+    pub q3: GraphValue,
+    pub upper_whisker: GraphValue,
+    pub max: GraphValue,
+    pub outliers: Vec<GraphValue>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> Value {
+        Value::test_date(DateTime::parse_from_rfc3339(date).unwrap())
+    }
+
+    #[test]
+    fn rejects_dates_that_cant_be_drawn() {
+        let value = GraphValue::from_value(date("2000-01-01T00:00:00Z")).unwrap();
+        assert_eq!(value.value, 946_684_800e9);
+        assert_eq!(value.unit, GraphUnit::Date);
+
+        assert!(GraphValue::from_value(date("1600-01-01T00:00:00Z")).is_err());
+        assert!(GraphValue::from_value(date("3000-01-01T00:00:00Z")).is_err());
+        assert!(matches!(
+            GraphValue::try_from_value(&Value::test_string("a")),
+            Ok(None)
+        ));
+    }
 }
@@ -0,0 +1,65 @@
+use unicode_width::UnicodeWidthChar;
+
+pub fn visible_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            c => width += c.width().unwrap_or_default(),
+        }
+    }
+
+    width
+}
+
+pub fn strip_ansi(s: &str) -> String {
+    let mut stripped = String::new();
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            c => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+pub fn pad_visible(s: &str, width: usize) -> String {
+    format!("{s}{}", " ".repeat(width.saturating_sub(visible_width(s))))
+}
+
+pub fn side_by_side(left: &str, right: &[String], gap: usize) -> String {
+    let left = left.lines().collect::<Vec<_>>();
+    let left_width = left
+        .iter()
+        .map(|line| visible_width(line))
+        .max()
+        .unwrap_or(0);
+
+    (0..left.len().max(right.len()))
+        .map(|i| {
+            let l = left.get(i).copied().unwrap_or_default();
+            match right.get(i) {
+                Some(r) => format!("{}{}{r}", pad_visible(l, left_width), " ".repeat(gap)),
+                None => l.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}